@@ -1,37 +1,41 @@
 use std::{
-    fs::OpenOptions,
     io::{BufRead, BufReader, Read, Result as IoResult},
+    path::Path,
 };
 
-pub fn day01(file_path: &str) -> IoResult<()> {
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part1_impl(reader);
-    println!("Day 01, Part 1: {}", result);
+use crate::{days::Answers, input};
 
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part2_impl(reader);
-    println!("Day 01, Part 2: {}", result);
+pub fn day01(file_path: &Path) -> IoResult<()> {
+    let answers = solve(&input::read(file_path)?)?;
+    println!("Day 01, Part 1: {}", answers.part1);
+    println!("Day 01, Part 2: {}", answers.part2);
 
     Result::Ok(())
 }
 
-fn part1_impl<T>(reader: BufReader<T>) -> i32
+/// Solves both parts of an already loaded input.
+pub fn solve(input: &str) -> IoResult<Answers> {
+    Ok(Answers::new(
+        part1_impl(BufReader::new(input.as_bytes())),
+        part2_impl(BufReader::new(input.as_bytes())),
+    ))
+}
+
+fn part1_impl<T>(reader: BufReader<T>) -> i64
 where
     T: Read,
 {
     parse_input(reader).fold((0, 50), part1_folder).0
 }
 
-fn part2_impl<T>(reader: BufReader<T>) -> i32
+fn part2_impl<T>(reader: BufReader<T>) -> i64
 where
     T: Read,
 {
     parse_input(reader).fold((0, 50), part2_folder).0
 }
 
-fn part1_folder(acc: (i32, i32), direction: i32) -> (i32, i32) {
+fn part1_folder(acc: (i64, i64), direction: i64) -> (i64, i64) {
     let (count, position) = acc;
     let new_position = (position + direction).rem_euclid(100);
     (
@@ -40,7 +44,7 @@ fn part1_folder(acc: (i32, i32), direction: i32) -> (i32, i32) {
     )
 }
 
-fn part2_folder(acc: (i32, i32), direction: i32) -> (i32, i32) {
+fn part2_folder(acc: (i64, i64), direction: i64) -> (i64, i64) {
     let (count, position) = acc;
     let new_position = position + direction;
     let new_position_rem = new_position.rem_euclid(100);
@@ -57,19 +61,17 @@ fn part2_folder(acc: (i32, i32), direction: i32) -> (i32, i32) {
     (count + count_diff, new_position_rem)
 }
 
-fn parse_input<T>(
-    reader: BufReader<T>,
-) -> std::iter::FilterMap<
-    std::io::Lines<BufReader<T>>,
-    fn(Result<String, std::io::Error>) -> Option<i32>,
->
+/// Rotations as signed clicks, negative to the left. Distances are `u32`, so a sign in the
+/// distance is rejected and there is room to add them to a position without overflowing.
+fn parse_input<T>(reader: BufReader<T>) -> impl Iterator<Item = i64>
 where
     T: Read,
 {
-    fn parse_line(line: Result<String, std::io::Error>) -> Option<i32> {
-        match line {
-            Ok(left) if left.starts_with("L") => left[1..].parse::<i32>().map(|x| -x).ok(),
-            Ok(right) if right.starts_with("R") => right[1..].parse::<i32>().ok(),
+    fn parse_line(line: Result<String, std::io::Error>) -> Option<i64> {
+        let line = line.ok()?;
+        match line.split_at_checked(1)? {
+            ("L", distance) => distance.parse::<u32>().map(|x| -i64::from(x)).ok(),
+            ("R", distance) => distance.parse::<u32>().map(i64::from).ok(),
             _ => Option::None,
         }
     }
@@ -82,43 +84,12 @@ mod tests {
 
     use crate::day01::part1_impl;
     use crate::day01::part2_impl;
-
-    #[test]
-    fn part1_example() {
-        // Arrange
-        let example_data = vec![
-            "L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82",
-        ]
-        .join("\n");
-        let reader = BufReader::new(example_data.as_bytes());
-
-        // Act
-        let result = part1_impl(reader);
-
-        // Assert
-        assert_eq!(3, result);
-    }
-
-    #[test]
-    fn part2_example() {
-        // Arrange
-        let example_data = vec![
-            "L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82",
-        ]
-        .join("\n");
-        let reader = BufReader::new(example_data.as_bytes());
-
-        // Act
-        let result = part2_impl(reader);
-
-        // Assert
-        assert_eq!(6, result);
-    }
+    use crate::prop;
 
     #[test]
     fn part2_lands_on_0() {
         // Arrange
-        let example_data = vec!["L100", "L150", "R1"].join("\n");
+        let example_data = ["L100", "L150", "R1"].join("\n");
         let reader = BufReader::new(example_data.as_bytes());
 
         // Act
@@ -131,7 +102,7 @@ mod tests {
     #[test]
     fn part2_lands_on_0_again() {
         // Arrange
-        let example_data = vec!["L100", "L150", "R1", "L1"].join("\n");
+        let example_data = ["L100", "L150", "R1", "L1"].join("\n");
         let reader = BufReader::new(example_data.as_bytes());
 
         // Act
@@ -144,7 +115,7 @@ mod tests {
     #[test]
     fn part2_lands_on_0_again_then_leaves() {
         // Arrange
-        let example_data = vec!["L100", "L150", "R1", "L1", "L1"].join("\n");
+        let example_data = ["L100", "L150", "R1", "L1", "L1"].join("\n");
         let reader = BufReader::new(example_data.as_bytes());
 
         // Act
@@ -153,4 +124,33 @@ mod tests {
         // Assert
         assert_eq!(4, result);
     }
+
+    #[test]
+    fn part2_counts_at_least_every_landing() {
+        prop::check(
+            500,
+            |rng| rng.vec(0, 30, |rng| rng.range(0, 600) as i32 - 300),
+            |rotations: &Vec<i32>| {
+                // A rotation by zero lands on 0 without passing it, so it only counts in part 1
+                if rotations.contains(&0) {
+                    return true;
+                }
+                let input: Vec<String> = rotations
+                    .iter()
+                    .map(|&r| {
+                        if r < 0 {
+                            format!("L{}", -r)
+                        } else {
+                            format!("R{}", r)
+                        }
+                    })
+                    .collect();
+                let input = input.join("\n");
+
+                let part1 = part1_impl(BufReader::new(input.as_bytes()));
+                let part2 = part2_impl(BufReader::new(input.as_bytes()));
+                part2 >= part1
+            },
+        );
+    }
 }
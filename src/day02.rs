@@ -1,87 +1,106 @@
 use std::{
-    collections::HashSet,
-    fs::OpenOptions,
     io::{BufRead, BufReader, Read, Result as IoResult},
+    path::Path,
 };
 
-pub fn day02(file_path: &str) -> IoResult<()> {
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part1_impl(reader);
-    println!("Day 02, Part 1: {}", result);
+use crate::{
+    days::Answers,
+    input,
+    interval_set::{IntervalSet, Range},
+};
+
+pub fn day02(file_path: &Path) -> IoResult<()> {
+    let answers = solve(&input::read(file_path)?)?;
+    println!("Day 02, Part 1: {}", answers.part1);
+    println!("Day 02, Part 2: {}", answers.part2);
 
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part2_impl(reader);
-    println!("Day 02, Part 2: {}", result);
+    Result::Ok(())
+}
 
-    Ok(())
+/// Solves both parts of an already loaded input.
+pub fn solve(input: &str) -> IoResult<Answers> {
+    Ok(Answers::new(
+        part1_impl(BufReader::new(input.as_bytes())),
+        part2_impl(BufReader::new(input.as_bytes())),
+    ))
 }
 
-fn part1_impl<T>(reader: BufReader<T>) -> u64
+fn part1_impl<T>(reader: BufReader<T>) -> u128
 where
     T: Read,
 {
     let ranges = parse(reader);
-    let max = ranges.iter().map(|r| r.end).max().unwrap_or_default();
-    let silly_ids = (1u32..)
-        .map(concat_digits)
-        .take_while(|x| *x <= max)
-        .filter(|n| ranges.iter().any(|r| r.contains(*n)));
 
-    silly_ids.sum()
+    ranges
+        .ranges()
+        .iter()
+        .flat_map(|range| {
+            (2..=MAX_DIGITS)
+                .step_by(2)
+                .map(|length| repeated_sum(range, length, length / 2))
+        })
+        .sum()
 }
 
-fn part2_impl<T>(reader: BufReader<T>) -> u64
+fn part2_impl<T>(reader: BufReader<T>) -> u128
 where
     T: Read,
 {
     let ranges = parse(reader);
-    let max = ranges.iter().map(|r| r.end).max().unwrap_or_default();
-
-    let silly_ids: HashSet<u64> = (1u32..)
-        .take_while(|i| concat_digits(*i) <= max)
-        .flat_map(|i| concat_digits_many(i).take_while(|x| *x <= max))
-        .take_while(|x| *x <= max)
-        .filter(|n| ranges.iter().any(|r| r.contains(*n)))
-        .collect();
-
-    silly_ids.iter().sum()
-}
+    let mut sum = 0i128;
+
+    for range in ranges.ranges() {
+        for length in 2..=MAX_DIGITS {
+            // Every repeated ID repeats one of the longest proper periods, `length / q` for a
+            // prime `q`; IDs sharing two such periods also repeat their gcd, so count those once
+            let primes: Vec<u32> = (2..=length)
+                .filter(|q| length.is_multiple_of(*q) && (2..*q).all(|d| !q.is_multiple_of(d)))
+                .collect();
+            for subset in 1..1u32 << primes.len() {
+                let product: u32 = (0..primes.len())
+                    .filter(|bit| subset & 1 << bit != 0)
+                    .map(|bit| primes[bit])
+                    .product();
+                let repeated = repeated_sum(range, length, length / product) as i128;
+                match subset.count_ones() % 2 {
+                    1 => sum += repeated,
+                    _ => sum -= repeated,
+                }
+            }
+        }
+    }
 
-fn concat_digits(i: u32) -> u64 {
-    let number_of_digits = i.ilog10() + 1;
-    let i: u64 = i.into();
-    10u64.pow(number_of_digits) * i + i
+    sum as u128
 }
 
-fn concat_digits_many(
-    i: u32,
-) -> std::iter::Scan<std::ops::RangeFrom<i32>, u64, impl FnMut(&mut u64, i32) -> Option<u64>> {
-    let number_of_digits = i.ilog10() + 1;
-    let i: u64 = i.into();
-    (0..).scan(i, move |x, _| {
-        *x = 10u64.pow(number_of_digits) * (*x) + i;
-        Some(*x)
-    })
+/// The digits of `u64::MAX`, so no ID in a range is any longer.
+const MAX_DIGITS: u32 = 20;
+
+/// The sum of the IDs in `range` made of `length` digits that repeat the same `period` digits
+/// over and over, found without walking the range.
+fn repeated_sum(range: &Range, length: u32, period: u32) -> u128 {
+    // Such an ID is its first `period` digits, the seed, times 100..0100..01
+    let multiplier = (10u128.pow(length) - 1) / (10u128.pow(period) - 1);
+    let first = u128::from(range.start)
+        .div_ceil(multiplier)
+        .max(10u128.pow(period - 1));
+    let last = (u128::from(range.end) / multiplier).min(10u128.pow(period) - 1);
+
+    if first <= last {
+        multiplier * (first + last) * (last - first + 1) / 2
+    } else {
+        0
+    }
 }
 
-fn parse<T>(mut reader: BufReader<T>) -> Vec<IdRange>
+fn parse<T>(mut reader: BufReader<T>) -> IntervalSet
 where
     T: Read,
 {
-    let mut res = vec![];
-
-    loop {
-        let range = parse_next(&mut reader);
-        if range.is_none() {
-            return res;
-        }
-        res.push(range.unwrap());
-    }
+    std::iter::from_fn(|| parse_next(&mut reader)).collect()
 }
 
-fn parse_next<T>(reader: &mut BufReader<T>) -> Option<IdRange>
+fn parse_next<T>(reader: &mut BufReader<T>) -> Option<Range>
 where
     T: Read,
 {
@@ -90,6 +109,7 @@ where
     let start_length = reader.read_until_before(b'-', &mut buf).ok()?;
     let start = str::from_utf8(&buf[..start_length])
         .ok()?
+        .trim()
         .parse::<u64>()
         .ok()?;
 
@@ -98,10 +118,11 @@ where
     let end_length = reader.read_until_before(b',', &mut buf).ok()?;
     let end = str::from_utf8(&buf[..end_length])
         .ok()?
+        .trim()
         .parse::<u64>()
         .ok()?;
 
-    Some(IdRange { start, end })
+    Some(Range { start, end })
 }
 
 trait ReadUntilBefore: BufRead {
@@ -123,55 +144,87 @@ where
     }
 }
 
-struct IdRange {
-    start: u64,
-    end: u64,
-}
-
-impl IdRange {
-    fn contains(&self, number: u64) -> bool {
-        self.start <= number && number <= self.end
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::io::BufReader;
 
     use crate::day02::part1_impl;
     use crate::day02::part2_impl;
+    use crate::prop;
+
+    /// Whether `id` is some digits repeated at least twice, or exactly twice for part 1.
+    fn is_repeated(id: u64, twice: bool) -> bool {
+        let digits = id.to_string();
+        let length = digits.len();
+        (1..length)
+            .filter(|period| length.is_multiple_of(*period))
+            .any(|period| {
+                (!twice || period * 2 == length)
+                    && digits
+                        .as_bytes()
+                        .chunks(period)
+                        .all(|chunk| *chunk == digits.as_bytes()[..period])
+            })
+    }
+
+    fn walk(start: u64, end: u64, twice: bool) -> u128 {
+        (start..=end)
+            .filter(|id| is_repeated(*id, twice))
+            .map(u128::from)
+            .sum()
+    }
 
     #[test]
-    fn part1_example() {
+    fn part1_trailing_newline() {
         // Arrange
-        let input = concat!(
-            "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,",
-            "1698522-1698528,446443-446449,38593856-38593862,565653-565659,",
-            "824824821-824824827,2121212118-2121212124"
-        );
+        let input = "11-22,95-115\n";
         let reader = BufReader::new(input.as_bytes());
 
         // Act
         let result = part1_impl(reader);
 
         // Assert
-        assert_eq!(1227775554, result);
+        assert_eq!(11 + 22 + 99, result);
     }
 
     #[test]
-    fn part2_example() {
-        // Arrange
-        let input = concat!(
-            "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,",
-            "1698522-1698528,446443-446449,38593856-38593862,565653-565659,",
-            "824824821-824824827,2121212118-2121212124"
+    fn sums_match_walking_the_range() {
+        prop::check(
+            200,
+            |rng| (rng.range(0, 2_000_000), rng.range(0, 3000)),
+            |&(start, width): &(u64, u64)| {
+                let input = format!("{}-{}", start, start + width);
+                let part1 = part1_impl(BufReader::new(input.as_bytes()));
+                let part2 = part2_impl(BufReader::new(input.as_bytes()));
+
+                part1 == walk(start, start + width, true)
+                    && part2 == walk(start, start + width, false)
+            },
         );
-        let reader = BufReader::new(input.as_bytes());
+    }
+
+    #[test]
+    fn whole_u64_range() {
+        // Arrange
+        let input = format!("0-{}", u64::MAX);
+        // Every seed of k digits doubles to seed * (10^k + 1), up to u64::MAX for k = 10
+        let doubled: u128 = (1..=10)
+            .map(|k| {
+                let multiplier = 10u128.pow(k) + 1;
+                let first = 10u128.pow(k - 1);
+                let last = (10u128.pow(k) - 1).min(u128::from(u64::MAX) / multiplier);
+                multiplier * (first + last) * (last - first + 1) / 2
+            })
+            .sum();
 
         // Act
-        let result = part2_impl(reader);
+        let part1 = part1_impl(BufReader::new(input.as_bytes()));
+        let part2 = part2_impl(BufReader::new(input.as_bytes()));
 
         // Assert
-        assert_eq!(4174379265, result);
+        assert_eq!(12_509_613_850_169_742_155_792_778_978, doubled);
+        assert_eq!(doubled, part1);
+        // Summed separately over the seeds of each length that do not repeat themselves
+        assert_eq!(12_510_120_345_498_176_585_131_248_687, part2);
     }
 }
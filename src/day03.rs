@@ -1,135 +1,722 @@
 use std::{
-    fs::OpenOptions,
-    io::{BufRead, BufReader, Read, Result as IoResult},
+    cmp::Ordering,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{BufRead, BufReader, Error as IoError, ErrorKind, Read, Result as IoResult, Write},
+    iter::Sum,
+    ops::Add,
+    path::Path,
 };
 
-pub fn day03(file_path: &str) -> IoResult<()> {
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part1_impl(reader);
-    println!("Day 03, Part 1: {}", result);
+use crate::{days::Answers, input};
 
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part2_impl(reader);
-    println!("Day 03, Part 2: {}", result);
+pub fn day03(file_path: &Path) -> IoResult<()> {
+    let answers = solve(&input::read(file_path)?)?;
+    println!("Day 03, Part 1: {}", answers.part1);
+    println!("Day 03, Part 2: {}", answers.part2);
 
     Result::Ok(())
 }
 
-fn part1_impl<T>(reader: BufReader<T>) -> u64
+/// Solves both parts of an already loaded input.
+pub fn solve(input: &str) -> IoResult<Answers> {
+    Ok(Answers::new(
+        part1_impl(BufReader::new(input.as_bytes()))?,
+        part2_impl(BufReader::new(input.as_bytes()))?,
+    ))
+}
+
+fn part1_impl<T>(reader: BufReader<T>) -> IoResult<Joltage>
 where
     T: Read,
 {
-    parse(reader).filter_map(|x| largest_joltage(&x, 2)).sum()
+    total_joltage(reader, 2, OnInvalid::Fail)
 }
 
-fn part2_impl<T>(reader: BufReader<T>) -> u64
+fn part2_impl<T>(reader: BufReader<T>) -> IoResult<Joltage>
 where
     T: Read,
 {
-    parse(reader).filter_map(|x| largest_joltage(&x, 12)).sum()
+    total_joltage(reader, 12, OnInvalid::Fail)
 }
 
-fn parse<T>(
+/// Sums the largest `digits`-digit joltage of every bank, for any number of digits.
+pub fn total_joltage<T>(
     reader: BufReader<T>,
-) -> std::iter::Map<
-    std::iter::FilterMap<
-        std::io::Lines<BufReader<T>>,
-        impl FnMut(Result<String, std::io::Error>) -> Option<String>,
-    >,
-    fn(String) -> Vec<u32>,
->
+    digits: usize,
+    on_invalid: OnInvalid,
+) -> IoResult<Joltage>
+where
+    T: Read,
+{
+    let banks = parse_banks(reader, digits, on_invalid)?;
+
+    Ok(banks
+        .banks
+        .iter()
+        .filter_map(|x| largest_joltage(x, digits))
+        .map(|selection| selection.joltage)
+        .sum())
+}
+
+/// What to do with a bank that cannot provide the requested joltage.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum OnInvalid {
+    /// Leave the bank out and report it in [`Banks::skipped`].
+    Skip,
+    /// Stop at the first bank without a joltage with an [`ErrorKind::InvalidData`] error.
+    Fail,
+}
+
+/// The valid banks of an input, and the problems with those that were skipped.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Banks {
+    pub banks: Vec<Vec<u32>>,
+    pub skipped: Vec<BankError>,
+}
+
+/// A problem with a bank, located by its 1-based line number.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum BankError {
+    /// The bank has fewer batteries than digits requested.
+    TooShort {
+        line: usize,
+        length: usize,
+        digits: usize,
+    },
+    /// The bank contains something other than a digit, at a 1-based column.
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// The bank's length differs from the first valid bank's.
+    InconsistentLength {
+        line: usize,
+        length: usize,
+        expected: usize,
+    },
+}
+
+impl Display for BankError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            BankError::TooShort {
+                line,
+                length,
+                digits,
+            } => write!(
+                f,
+                "line {}: bank has {} batteries, but {} digits are needed",
+                line, length, digits
+            ),
+            BankError::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} is not a battery",
+                line, column, character
+            ),
+            BankError::InconsistentLength {
+                line,
+                length,
+                expected,
+            } => write!(
+                f,
+                "line {}: bank has {} batteries, but earlier banks have {}",
+                line, length, expected
+            ),
+        }
+    }
+}
+
+impl Error for BankError {}
+
+impl From<BankError> for IoError {
+    fn from(error: BankError) -> Self {
+        IoError::new(ErrorKind::InvalidData, error)
+    }
+}
+
+/// Lists every problem with the banks of an input, without stopping at the first.
+pub fn validate<T>(reader: BufReader<T>, digits: usize) -> IoResult<Vec<BankError>>
+where
+    T: Read,
+{
+    Ok(parse_banks(reader, digits, OnInvalid::Skip)?.skipped)
+}
+
+/// Parses the banks of an input, checking that each one only holds digits and has at least
+/// `digits` batteries. Blank lines are ignored.
+///
+/// When skipping, a bank that is not as long as the first valid bank is reported and left
+/// out as well; when failing, it is kept, since its joltage is still well defined.
+pub fn parse_banks<T>(reader: BufReader<T>, digits: usize, on_invalid: OnInvalid) -> IoResult<Banks>
+where
+    T: Read,
+{
+    let mut banks = Banks::default();
+    let mut expected = None;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let bank = match check_bank(index + 1, &line, digits) {
+            Ok(bank) => bank,
+            Err(error) if on_invalid == OnInvalid::Skip => {
+                banks.skipped.push(error);
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        // Banks of different lengths still have a joltage, so only skipping reports them
+        let expected = *expected.get_or_insert(bank.len());
+        if bank.len() != expected && on_invalid == OnInvalid::Skip {
+            banks.skipped.push(BankError::InconsistentLength {
+                line: index + 1,
+                length: bank.len(),
+                expected,
+            });
+            continue;
+        }
+        banks.banks.push(bank);
+    }
+
+    Ok(banks)
+}
+
+fn check_bank(line_number: usize, line: &str, digits: usize) -> Result<Vec<u32>, BankError> {
+    let bank = line
+        .chars()
+        .enumerate()
+        .map(|(column, c)| {
+            c.to_digit(10).ok_or(BankError::InvalidCharacter {
+                line: line_number,
+                column: column + 1,
+                character: c,
+            })
+        })
+        .collect::<Result<Vec<u32>, BankError>>()?;
+
+    if bank.len() < digits {
+        return Err(BankError::TooShort {
+            line: line_number,
+            length: bank.len(),
+            digits,
+        });
+    }
+
+    Ok(bank)
+}
+
+/// Reads every line of an input that is meant to be shown as it is.
+///
+/// A line that is not valid UTF-8 is skipped, while a failing reader ends the input, as it
+/// would keep failing on every further line.
+fn raw_lines<T>(reader: BufReader<T>) -> impl Iterator<Item = String>
 where
     T: Read,
 {
-    reader.lines().filter_map(IoResult::ok).map(parse_line)
+    reader
+        .lines()
+        .take_while(|line| !matches!(line, Err(error) if error.kind() != ErrorKind::InvalidData))
+        .filter_map(|line| line.ok())
+}
+
+/// Picks the `digits` batteries forming the largest number, keeping their order.
+///
+/// This is the classic "remove n - k digits to maximise" greedy: walking the bank once,
+/// a smaller digit on top of the stack is dropped whenever a larger one arrives and we
+/// can still afford to drop digits. Every digit is pushed and popped at most once.
+pub fn largest_joltage(line: &[u32], digits: usize) -> Option<Selection> {
+    let positions = monotonic_selection(line, 0, digits, |top, digit| top < digit)?;
+    Some(Selection::new(line, positions))
 }
 
-fn parse_line(line: String) -> Vec<u32> {
-    line.chars().filter_map(|c| char::to_digit(c, 10)).collect()
+/// Whether the smallest joltage may start with a battery showing `0`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LeadingZeros {
+    Allowed,
+    Forbidden,
 }
 
-fn largest_joltage(line: &[u32], digits: usize) -> Option<u64> {
+/// Picks the `digits` batteries forming the smallest number, keeping their order.
+///
+/// With [`LeadingZeros::Forbidden`] the first battery must be non-zero, so this is `None`
+/// if no non-zero battery leaves enough room for the rest.
+pub fn smallest_joltage(
+    line: &[u32],
+    digits: usize,
+    leading_zeros: LeadingZeros,
+) -> Option<Selection> {
     if digits > line.len() {
         return None;
     }
 
-    if digits == 1 {
-        return line.iter().map(|x| (*x).into()).max();
+    let positions = match leading_zeros {
+        _ if digits == 0 => vec![],
+        LeadingZeros::Allowed => monotonic_selection(line, 0, digits, |top, digit| top > digit)?,
+        LeadingZeros::Forbidden => {
+            // The first battery is the earliest smallest non-zero one that leaves room for
+            // the rest, after which zeros are fine again.
+            let (first, _) = line[..=(line.len() - digits)]
+                .iter()
+                .enumerate()
+                .filter(|(_, digit)| **digit != 0)
+                .min_by_key(|(index, digit)| (**digit, *index))?;
+            let mut positions =
+                monotonic_selection(line, first + 1, digits - 1, |top, digit| top > digit)?;
+            positions.insert(0, first);
+            positions
+        }
+    };
+
+    Some(Selection::new(line, positions))
+}
+
+/// Picks `digits` positions from `line[from..]`, popping the top of the stack while
+/// `replace(top, digit)` holds and we can still afford to drop batteries.
+fn monotonic_selection<F>(
+    line: &[u32],
+    from: usize,
+    digits: usize,
+    replace: F,
+) -> Option<Vec<usize>>
+where
+    F: Fn(u32, u32) -> bool,
+{
+    let available = line.len().checked_sub(from)?;
+    let mut removable = available.checked_sub(digits)?;
+    let mut stack: Vec<usize> = Vec::with_capacity(available);
+
+    for (index, &digit) in line.iter().enumerate().skip(from) {
+        while removable > 0 && stack.last().is_some_and(|&top| replace(line[top], digit)) {
+            stack.pop();
+            removable -= 1;
+        }
+        stack.push(index);
     }
 
-    if digits <= 0 {
-        return Some(0);
+    // Whatever we could not remove while scanning is cut from the end,
+    // where the digits are the least useful.
+    stack.truncate(digits);
+
+    Some(stack)
+}
+
+/// Picks the `n`-th largest distinct `digits`-digit joltage, counting from `0`.
+///
+/// Subsequences showing the same digits count once, and their positions are the earliest
+/// ones producing those digits. `None` if there are not more than `n` distinct joltages.
+pub fn nth_largest_joltage(line: &[u32], digits: usize, n: u128) -> Option<Selection> {
+    if digits > line.len() {
+        return None;
     }
 
-    let digits = digits - 1;
+    let counts = SubsequenceCounts::new(line, digits);
+    let mut rank = n;
+    let mut from = 0;
+    let mut positions = Vec::with_capacity(digits);
 
-    let (index, first_digit): (usize, u64) = line[..(line.len() - digits)]
-        .iter()
-        .map(|x| (*x).into())
-        .enumerate()
-        // Find the max value, with the smallest index
-        // Would we not explicitly specify `line.len() - index`, the last occurence would be picked
-        // We are using that tuples are ordered lexicographically
-        .max_by_key(|(index, value)| (*value, line.len() - *index))?;
+    for remaining in (0..digits).rev() {
+        let (position, skipped) = (0..=9)
+            .rev()
+            .filter_map(|digit| counts.next(from, digit))
+            .scan(0_u128, |skipped, position| {
+                let before = *skipped;
+                *skipped = skipped.saturating_add(counts.count(position + 1, remaining));
+                Some((position, before, *skipped))
+            })
+            .find(|(_, _, skipped)| rank < *skipped)
+            .map(|(position, before, _)| (position, before))?;
+
+        rank -= skipped;
+        positions.push(position);
+        from = position + 1;
+    }
+
+    (rank == 0).then(|| Selection::new(line, positions))
+}
+
+/// Counts the distinct `digits`-digit joltages of a bank that are strictly greater than
+/// `threshold`.
+///
+/// The count saturates at `u128::MAX`, which only long banks with many digits can reach.
+pub fn count_joltages_above(line: &[u32], digits: usize, threshold: &Joltage) -> u128 {
+    let threshold = threshold.significant_digits();
+
+    if digits > line.len() || threshold.len() > digits {
+        return 0;
+    }
+
+    // Pad the threshold with leading zeros so it compares digit by digit.
+    let threshold: Vec<u32> = std::iter::repeat_n(0, digits - threshold.len())
+        .chain(threshold.iter().copied())
+        .collect();
+
+    let counts = SubsequenceCounts::new(line, digits);
+    let mut total: u128 = 0;
+    let mut from = 0;
+
+    for (index, &bound) in threshold.iter().enumerate() {
+        let remaining = digits - index - 1;
+
+        // Every joltage matching the threshold so far and exceeding it here is larger.
+        for digit in (bound + 1)..=9 {
+            if let Some(position) = counts.next(from, digit) {
+                total = total.saturating_add(counts.count(position + 1, remaining));
+            }
+        }
+
+        match counts.next(from, bound) {
+            Some(position) => from = position + 1,
+            None => break,
+        }
+    }
+
+    total
+}
+
+/// Number of distinct subsequences of each length in every suffix of a bank.
+///
+/// Always extending with the earliest next occurrence of a digit enumerates each distinct
+/// subsequence exactly once.
+struct SubsequenceCounts {
+    /// `next[i][d]` is the first position at or after `i` showing digit `d`.
+    next: Vec<[Option<usize>; 10]>,
+    /// `counts[l][i]` is the number of distinct subsequences of length `l` in `line[i..]`.
+    counts: Vec<Vec<u128>>,
+}
+
+impl SubsequenceCounts {
+    fn new(line: &[u32], digits: usize) -> Self {
+        let mut next = vec![[None; 10]; line.len() + 1];
+        for (index, &digit) in line.iter().enumerate().rev() {
+            next[index] = next[index + 1];
+            next[index][digit as usize] = Some(index);
+        }
+
+        let mut counts = vec![vec![1_u128; line.len() + 1]];
+        for length in 1..=digits {
+            let shorter = &counts[length - 1];
+            let current = next
+                .iter()
+                .map(|following| {
+                    following.iter().flatten().fold(0_u128, |acc, &position| {
+                        acc.saturating_add(shorter[position + 1])
+                    })
+                })
+                .collect();
+            counts.push(current);
+        }
+
+        SubsequenceCounts { next, counts }
+    }
+
+    fn next(&self, from: usize, digit: u32) -> Option<usize> {
+        self.next[from][digit as usize]
+    }
+
+    fn count(&self, from: usize, length: usize) -> u128 {
+        self.counts[length][from]
+    }
+}
+
+/// The batteries switched on in a bank and the joltage they produce.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Selection {
+    /// Indices into the bank, in ascending order.
+    pub positions: Vec<usize>,
+    pub joltage: Joltage,
+}
+
+impl Selection {
+    fn new(line: &[u32], positions: Vec<usize>) -> Self {
+        let digits = positions.iter().map(|&i| line[i]).collect();
+        Selection {
+            positions,
+            joltage: Joltage { digits },
+        }
+    }
+}
+
+/// How the selected batteries are marked when rendering a bank.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Highlight {
+    /// Bold green ANSI escape codes, for terminals.
+    Ansi,
+    /// Square brackets around each selected digit, e.g. `9[8]1`.
+    Brackets,
+}
+
+/// Renders a bank with the batteries at `positions` highlighted.
+pub fn render_selection(line: &[u32], positions: &[usize], highlight: Highlight) -> String {
+    let mut selected = positions.iter().peekable();
+    let mut rendered = String::with_capacity(line.len() * 2);
+
+    for (index, digit) in line.iter().enumerate() {
+        let selected = selected.next_if_eq(&&index).is_some();
+        rendered.push_str(&mark(digit, selected, highlight));
+    }
+
+    rendered
+}
+
+fn mark(battery: impl Display, selected: bool, highlight: Highlight) -> String {
+    match (selected, highlight) {
+        (false, _) => battery.to_string(),
+        (true, Highlight::Ansi) => format!("\x1b[1;32m{}\x1b[0m", battery),
+        (true, Highlight::Brackets) => format!("[{}]", battery),
+    }
+}
+
+/// Writes every line of the input with its `digits` largest batteries highlighted, followed by
+/// the joltage.
+///
+/// Lines are written as they were read, so anything besides digits stays where it was and
+/// is not a battery. Lines with fewer than `digits` batteries are written unmarked.
+pub fn render<T, W>(
+    reader: BufReader<T>,
+    digits: usize,
+    highlight: Highlight,
+    out: &mut W,
+) -> IoResult<()>
+where
+    T: Read,
+    W: Write,
+{
+    for line in raw_lines(reader) {
+        // Where each battery sits in the line, so the selection can be marked in place
+        let (offsets, bank): (Vec<usize>, Vec<u32>) = line
+            .char_indices()
+            .filter_map(|(offset, c)| Some((offset, c.to_digit(10)?)))
+            .unzip();
+
+        let Some(selection) = largest_joltage(&bank, digits) else {
+            writeln!(out, "{}", line)?;
+            continue;
+        };
+
+        let mut selected = selection.positions.iter().map(|&i| offsets[i]).peekable();
+        let rendered: String = line
+            .char_indices()
+            .map(|(offset, c)| mark(c, selected.next_if_eq(&offset).is_some(), highlight))
+            .collect();
+        writeln!(out, "{} -> {}", rendered, selection.joltage)?;
+    }
+
+    Ok(())
+}
+
+/// A joltage of arbitrary length, stored as its decimal digits, most significant first.
+///
+/// Joltages compare by value, so leading zeros make no difference.
+#[derive(Clone, Debug, Default)]
+pub struct Joltage {
+    digits: Vec<u32>,
+}
 
-    let head: u64 = first_digit * 10_u64.pow(digits.try_into().unwrap());
-    let rest = largest_joltage(&line[(index + 1)..], digits)?;
-    Some(head + rest)
+impl Joltage {
+    pub fn digits(&self) -> &[u32] {
+        &self.digits
+    }
+
+    /// The digits without leading zeros, empty for a joltage of zero.
+    fn significant_digits(&self) -> &[u32] {
+        let leading_zeros = self.digits.iter().take_while(|&&digit| digit == 0).count();
+        &self.digits[leading_zeros..]
+    }
+
+    /// The joltage as a `u64`, or `None` if it does not fit.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.digits.iter().try_fold(0_u64, |acc, &digit| {
+            acc.checked_mul(10)?.checked_add(digit.into())
+        })
+    }
+}
+
+impl PartialEq for Joltage {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_digits() == other.significant_digits()
+    }
+}
+
+impl Eq for Joltage {}
+
+impl Ord for Joltage {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (left, right) = (self.significant_digits(), other.significant_digits());
+        // Without leading zeros, the longer number is the larger one
+        left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+    }
+}
+
+impl PartialOrd for Joltage {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u64> for Joltage {
+    fn from(value: u64) -> Self {
+        let digits = value
+            .to_string()
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .collect();
+        Joltage { digits }
+    }
+}
+
+impl Display for Joltage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let significant = self.significant_digits();
+
+        if significant.is_empty() {
+            return write!(f, "0");
+        }
+
+        for digit in significant {
+            write!(f, "{}", digit)?;
+        }
+        Ok(())
+    }
+}
+
+impl Add<&Joltage> for Joltage {
+    type Output = Joltage;
+
+    fn add(self, rhs: &Joltage) -> Joltage {
+        let mut digits = Vec::with_capacity(self.digits.len().max(rhs.digits.len()) + 1);
+        let mut left = self.digits.iter().rev();
+        let mut right = rhs.digits.iter().rev();
+        let mut carry = 0;
+
+        loop {
+            let (l, r) = (left.next(), right.next());
+            if l.is_none() && r.is_none() {
+                break;
+            }
+            let sum = l.unwrap_or(&0) + r.unwrap_or(&0) + carry;
+            digits.push(sum % 10);
+            carry = sum / 10;
+        }
+
+        if carry > 0 {
+            digits.push(carry);
+        }
+
+        digits.reverse();
+        Joltage { digits }
+    }
+}
+
+impl Sum for Joltage {
+    fn sum<I>(iter: I) -> Self
+    where
+        I: Iterator<Item = Joltage>,
+    {
+        // Stay on u64 for as long as the total fits, which covers the usual puzzle sizes,
+        // and only fall back to digit-wise addition once it overflows.
+        let mut fast = Some(0_u64);
+        let mut slow = Joltage::default();
+
+        for joltage in iter {
+            match fast
+                .zip(joltage.to_u64())
+                .and_then(|(a, b)| a.checked_add(b))
+            {
+                Some(total) => fast = Some(total),
+                None => {
+                    if let Some(total) = fast.take() {
+                        slow = Joltage::from(total);
+                    }
+                    slow = slow + &joltage;
+                }
+            }
+        }
+
+        fast.map(Joltage::from).unwrap_or(slow)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
     use std::io::BufReader;
 
+    use crate::day03::BankError;
+    use crate::day03::Highlight;
+    use crate::day03::Joltage;
+    use crate::day03::LeadingZeros;
+    use crate::day03::OnInvalid;
+    use crate::day03::count_joltages_above;
     use crate::day03::largest_joltage;
-    use crate::day03::part1_impl;
-    use crate::day03::part2_impl;
-
-    #[test]
-    fn part1_example() {
-        // Arrange
-        let test_input = vec![
-            "987654321111111",
-            "811111111111119",
-            "234234234234278",
-            "818181911112111",
-        ]
-        .join("\n");
+    use crate::day03::nth_largest_joltage;
+    use crate::day03::parse_banks;
+    use crate::day03::render;
+    use crate::day03::render_selection;
+    use crate::day03::smallest_joltage;
+    use crate::day03::solve;
+    use crate::day03::total_joltage;
+    use crate::day03::validate;
+    use crate::days::Answers;
+    use crate::prop;
+    use crate::rng::Rng;
 
-        let reader = BufReader::new(test_input.as_bytes());
+    /// The original recursive implementation, kept as an oracle for `largest_joltage`.
+    fn largest_joltage_recursive(line: &[u32], digits: usize) -> Option<u64> {
+        if digits > line.len() {
+            return None;
+        }
 
-        // Act
-        let result = part1_impl(reader);
+        if digits == 1 {
+            return line.iter().map(|x| (*x).into()).max();
+        }
 
-        // Assert
-        assert_eq!(357, result);
-    }
+        if digits == 0 {
+            return Some(0);
+        }
 
-    #[test]
-    fn part2_example() {
-        // Arrange
-        let test_input = vec![
-            "987654321111111",
-            "811111111111119",
-            "234234234234278",
-            "818181911112111",
-        ]
-        .join("\n");
+        let digits = digits - 1;
 
-        let reader = BufReader::new(test_input.as_bytes());
+        let (index, first_digit): (usize, u64) = line[..(line.len() - digits)]
+            .iter()
+            .map(|x| (*x).into())
+            .enumerate()
+            // Find the max value, with the smallest index
+            // Would we not explicitly specify `line.len() - index`, the last occurence would be picked
+            // We are using that tuples are ordered lexicographically
+            .max_by_key(|(index, value)| (*value, line.len() - *index))?;
 
-        // Act
-        let result = part2_impl(reader);
+        let head: u64 = first_digit * 10_u64.pow(digits.try_into().unwrap());
+        let rest = largest_joltage_recursive(&line[(index + 1)..], digits)?;
+        Some(head + rest)
+    }
 
-        // Assert
-        assert_eq!(3121910778619, result);
+    /// Every distinct `digits`-digit subsequence of a bank, by brute force.
+    fn all_joltages(line: &[u32], digits: usize) -> BTreeSet<Vec<u32>> {
+        (0_u32..(1 << line.len()))
+            .filter(|mask| mask.count_ones() as usize == digits)
+            .map(|mask| {
+                (0..line.len())
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| line[i])
+                    .collect()
+            })
+            .collect()
     }
+
     #[test]
     fn largest_joltage_one_digit() {
         // Act
-        let result = largest_joltage(&[1_u32], 1);
+        let result = largest_joltage(&[1_u32], 1).and_then(|s| s.joltage.to_u64());
 
         // Assert
         assert_eq!(Some(1_u64), result);
@@ -138,7 +725,7 @@ mod tests {
     #[test]
     fn largest_joltage_two_digits_same_digit() {
         // Act
-        let result = largest_joltage(&[1_u32, 1_u32], 2);
+        let result = largest_joltage(&[1_u32, 1_u32], 2).and_then(|s| s.joltage.to_u64());
 
         // Assert
         assert_eq!(Some(11_u64), result);
@@ -147,7 +734,7 @@ mod tests {
     #[test]
     fn largest_joltage_two_digits_no_choice() {
         // Act
-        let result = largest_joltage(&[1_u32, 9_u32], 2);
+        let result = largest_joltage(&[1_u32, 9_u32], 2).and_then(|s| s.joltage.to_u64());
 
         // Assert
         assert_eq!(Some(19_u64), result);
@@ -156,7 +743,7 @@ mod tests {
     #[test]
     fn largest_joltage_two_digits_choice() {
         // Act
-        let result = largest_joltage(&[9_u32, 1_u32, 9_u32], 2);
+        let result = largest_joltage(&[9_u32, 1_u32, 9_u32], 2).and_then(|s| s.joltage.to_u64());
 
         // Assert
         assert_eq!(Some(99_u64), result);
@@ -167,21 +754,362 @@ mod tests {
         assert_eq!(
             Some(987654321111_u64),
             largest_joltage(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 1, 1, 1, 1, 1, 1], 12)
+                .and_then(|s| s.joltage.to_u64())
         );
 
         assert_eq!(
             Some(811111111119_u64),
             largest_joltage(&[8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 9], 12)
+                .and_then(|s| s.joltage.to_u64())
         );
 
         assert_eq!(
             Some(434234234278_u64),
             largest_joltage(&[2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 7, 8], 12)
+                .and_then(|s| s.joltage.to_u64())
         );
 
         assert_eq!(
             Some(888911112111_u64),
             largest_joltage(&[8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1], 12)
+                .and_then(|s| s.joltage.to_u64())
+        );
+    }
+
+    #[test]
+    fn largest_joltage_matches_recursive() {
+        let banks: [&[u32]; 6] = [
+            &[9, 8, 7, 6, 5, 4, 3, 2, 1, 1, 1, 1, 1, 1, 1],
+            &[8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 9],
+            &[2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 3, 4, 2, 7, 8],
+            &[8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1],
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 9, 8, 7, 6, 5, 4],
+            &[5, 5, 5, 1, 5, 5, 5, 9, 5, 5, 5, 5, 2, 5, 5],
+        ];
+
+        for bank in banks {
+            for digits in 0..=bank.len() + 1 {
+                assert_eq!(
+                    largest_joltage_recursive(bank, digits),
+                    largest_joltage(bank, digits).and_then(|s| s.joltage.to_u64()),
+                    "bank {bank:?} with {digits} digits"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn largest_joltage_beyond_u64() {
+        // Arrange
+        let bank: Vec<u32> = (0..100).map(|i| (i * 7 % 10) as u32).collect();
+
+        // Act
+        let result = largest_joltage(&bank, 50).unwrap().joltage;
+
+        // Assert
+        assert_eq!(50, result.digits().len());
+        assert_eq!(None, result.to_u64());
+        assert_eq!(
+            "99999859630741852963074185296307418529630741852963",
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn total_joltage_overflowing_u64() {
+        // Arrange
+        let test_input = ["9".repeat(25), "1".repeat(25)].join("\n");
+        let reader = BufReader::new(test_input.as_bytes());
+
+        // Act
+        let result = total_joltage(reader, 20, OnInvalid::Fail).unwrap();
+
+        // Assert
+        assert_eq!("111111111111111111110", result.to_string());
+    }
+
+    #[test]
+    fn joltage_display_strips_leading_zeros() {
+        assert_eq!("0", Joltage::default().to_string());
+        assert_eq!(
+            "0",
+            largest_joltage(&[0, 0], 2).unwrap().joltage.to_string()
+        );
+        assert_eq!(
+            "7",
+            largest_joltage(&[0, 7], 2).unwrap().joltage.to_string()
+        );
+        assert_eq!("1234", Joltage::from(1234).to_string());
+    }
+
+    #[test]
+    fn joltage_compares_by_value() {
+        // Arrange
+        let padded = largest_joltage(&[0, 0, 7], 3).unwrap().joltage;
+        let zero = largest_joltage(&[0, 0], 2).unwrap().joltage;
+
+        // Act / Assert
+        assert_eq!(Joltage::default(), Joltage::from(0));
+        assert_eq!(Joltage::from(0), zero);
+        assert_eq!(Joltage::from(7), padded);
+        assert!(padded < Joltage::from(10));
+        assert!(Joltage::from(98) > Joltage::from(89));
+        assert!(Joltage::default() < padded);
+    }
+
+    #[test]
+    fn largest_joltage_positions() {
+        // Act
+        let result = largest_joltage(&[8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2, 1, 1, 1], 12).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+            result.positions
+        );
+    }
+
+    #[test]
+    fn render_selection_highlights() {
+        assert_eq!(
+            "[9]1[9]",
+            render_selection(&[9, 1, 9], &[0, 2], Highlight::Brackets)
+        );
+        assert_eq!(
+            "1\x1b[1;32m9\x1b[0m",
+            render_selection(&[1, 9], &[1], Highlight::Ansi)
+        );
+    }
+
+    #[test]
+    fn render_example() {
+        // Arrange
+        let test_input = ["987654321111111", "811111111111119", "1"].join("\n");
+        let mut out = vec![];
+
+        // Act
+        render(
+            BufReader::new(test_input.as_bytes()),
+            2,
+            Highlight::Brackets,
+            &mut out,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            "[9][8]7654321111111 -> 98\n[8]1111111111111[9] -> 89\n1\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn render_skips_lines_that_are_not_utf8() {
+        // Arrange
+        let test_input = b"91\n\xff\xfe\n19\n";
+        let mut out = vec![];
+
+        // Act
+        render(
+            BufReader::new(&test_input[..]),
+            1,
+            Highlight::Brackets,
+            &mut out,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!("[9]1 -> 9\n1[9] -> 9\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn render_keeps_the_line_as_read() {
+        // Arrange
+        let test_input = "9x8\n 7 1\n€3\n";
+        let mut out = vec![];
+
+        // Act
+        render(
+            BufReader::new(test_input.as_bytes()),
+            2,
+            Highlight::Brackets,
+            &mut out,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            "[9]x[8] -> 98\n [7] [1] -> 71\n€3\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn smallest_joltage_leading_zeros() {
+        // Arrange
+        let bank = [3, 0, 2, 0, 1, 9];
+
+        // Act
+        let allowed = smallest_joltage(&bank, 3, LeadingZeros::Allowed).unwrap();
+        let forbidden = smallest_joltage(&bank, 3, LeadingZeros::Forbidden).unwrap();
+
+        // Assert
+        assert_eq!(&[0, 0, 1], allowed.joltage.digits());
+        assert_eq!(vec![1, 3, 4], allowed.positions);
+        assert_eq!(&[2, 0, 1], forbidden.joltage.digits());
+        assert_eq!(
+            None,
+            smallest_joltage(&[0, 0, 5], 2, LeadingZeros::Forbidden)
+        );
+    }
+
+    #[test]
+    fn joltage_queries_match_brute_force() {
+        let banks: [&[u32]; 4] = [
+            &[8, 1, 8, 1, 8, 1, 9, 1, 1, 1, 1, 2],
+            &[2, 3, 4, 2, 3, 4, 2, 3, 0, 2],
+            &[5, 5, 5, 5, 5, 5],
+            &[1, 0, 9, 0, 1, 0, 7, 3],
+        ];
+
+        for bank in banks {
+            for digits in 0..=bank.len() {
+                let expected = all_joltages(bank, digits);
+
+                assert_eq!(
+                    expected.first().map(Vec::as_slice),
+                    smallest_joltage(bank, digits, LeadingZeros::Allowed)
+                        .as_ref()
+                        .map(|s| s.joltage.digits())
+                );
+
+                for (n, joltage) in expected.iter().rev().enumerate() {
+                    let selection = nth_largest_joltage(bank, digits, n as u128).unwrap();
+                    assert_eq!(joltage.as_slice(), selection.joltage.digits());
+
+                    let threshold = joltage.iter().fold(0, |acc, &d| acc * 10 + u64::from(d));
+                    assert_eq!(
+                        n as u128,
+                        count_joltages_above(bank, digits, &Joltage::from(threshold))
+                    );
+                }
+                assert_eq!(
+                    None,
+                    nth_largest_joltage(bank, digits, expected.len() as u128)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        // Arrange
+        let test_input = ["98765", "1", "12a45", "", "1234567", "11111"].join("\n");
+
+        // Act
+        let result = validate(BufReader::new(test_input.as_bytes()), 2).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                BankError::TooShort {
+                    line: 2,
+                    length: 1,
+                    digits: 2
+                },
+                BankError::InvalidCharacter {
+                    line: 3,
+                    column: 3,
+                    character: 'a'
+                },
+                BankError::InconsistentLength {
+                    line: 5,
+                    length: 7,
+                    expected: 5
+                },
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn parse_banks_skip_or_fail() {
+        // Arrange
+        let test_input = ["98765", "1", "11111"].join("\n");
+
+        // Act
+        let skipped =
+            parse_banks(BufReader::new(test_input.as_bytes()), 2, OnInvalid::Skip).unwrap();
+        let failed = parse_banks(BufReader::new(test_input.as_bytes()), 2, OnInvalid::Fail);
+
+        // Assert
+        assert_eq!(
+            vec![vec![9, 8, 7, 6, 5], vec![1, 1, 1, 1, 1]],
+            skipped.banks
+        );
+        assert_eq!(1, skipped.skipped.len());
+        assert_eq!(
+            "line 2: bank has 1 batteries, but 2 digits are needed",
+            failed.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn banks_of_different_lengths_are_solved() {
+        // Arrange
+        let test_input = ["987654321111111", "8111111111111191"].join("\n");
+
+        // Act
+        let answers = solve(&test_input).unwrap();
+        let skipped =
+            parse_banks(BufReader::new(test_input.as_bytes()), 2, OnInvalid::Skip).unwrap();
+
+        // Assert
+        assert_eq!(
+            Answers::new(98 + 91, 987_654_321_111_u64 + 811_111_111_191),
+            answers
+        );
+        assert_eq!(
+            vec![BankError::InconsistentLength {
+                line: 2,
+                length: 16,
+                expected: 15
+            }],
+            skipped.skipped
+        );
+    }
+
+    #[test]
+    fn largest_joltage_beats_sampled_subsequences() {
+        prop::check(
+            500,
+            |rng| {
+                let line = rng.vec(0, 20, |rng| rng.range(0, 9) as u32);
+                (line, rng.range(1, 8) as usize, rng.next_u64())
+            },
+            |(line, digits, seed): &(Vec<u32>, usize, u64)| {
+                let Some(selection) = largest_joltage(line, *digits) else {
+                    return line.len() < *digits;
+                };
+
+                let picked: Vec<u32> = selection.positions.iter().map(|&i| line[i]).collect();
+                let is_subsequence = selection.positions.len() == *digits
+                    && selection.positions.windows(2).all(|w| w[0] < w[1])
+                    && picked == selection.joltage.digits();
+
+                // Equally long digit strings compare like the numbers they spell
+                let mut rng = Rng::new(*seed);
+                let beats_samples = (0..20).all(|_| {
+                    let mut positions: Vec<usize> = (0..line.len()).collect();
+                    while positions.len() > *digits {
+                        positions.remove(rng.range(0, positions.len() as u64 - 1) as usize);
+                    }
+                    let sample: Vec<u32> = positions.iter().map(|&i| line[i]).collect();
+                    picked >= sample
+                });
+
+                is_subsequence && beats_samples
+            },
         );
     }
 }
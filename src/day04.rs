@@ -1,144 +1,749 @@
 use std::{
-    cmp::{max, min},
-    fs::OpenOptions,
-    io::{BufRead, BufReader, Read, Result as IoResult},
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    io::{
+        BufRead, BufReader, BufWriter, Error as IoError, ErrorKind, Read, Result as IoResult, Write,
+    },
+    path::{Path, PathBuf},
 };
 
-pub fn day04(file_path: &str) -> IoResult<()> {
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part1_impl(reader);
-    println!("Day 04, Part 1: {}", result);
+use crate::{
+    days::Answers,
+    grid::{Boundary, Grid, Neighbourhood},
+    input,
+};
 
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part2_impl(reader);
-    println!("Day 04, Part 2: {}", result);
+pub fn day04(file_path: &Path) -> IoResult<()> {
+    let answers = solve(&input::read(file_path)?)?;
+    println!("Day 04, Part 1: {}", answers.part1);
+    println!("Day 04, Part 2: {}", answers.part2);
 
     Result::Ok(())
 }
 
-fn part1_impl<T>(reader: BufReader<T>) -> usize
+/// Solves both parts of an already loaded input.
+pub fn solve(input: &str) -> IoResult<Answers> {
+    Ok(Answers::new(
+        part1_impl(BufReader::new(input.as_bytes()))?,
+        part2_impl(BufReader::new(input.as_bytes()))?,
+    ))
+}
+
+fn part1_impl<T>(reader: BufReader<T>) -> IoResult<usize>
 where
     T: Read,
 {
-    let mut map = parse(reader);
+    let mut map = parse(reader)?;
 
-    remove_accessible(&mut map)
+    Ok(remove_accessible(&mut map, &Rule::default()))
 }
 
-fn part2_impl<T>(reader: BufReader<T>) -> usize
+fn part2_impl<T>(reader: BufReader<T>) -> IoResult<usize>
 where
     T: Read,
 {
-    let mut map = parse(reader);
-    let mut removed_total: usize = 0;
-
-    loop {
-        let removed = remove_accessible(&mut map);
-        if removed > 0 {
-            removed_total += removed;
-        } else {
-            return removed_total;
+    let mut map = parse(reader)?;
+
+    Ok(remove_all(&mut map, &Rule::default()))
+}
+
+/// When a roll counts as accessible: fewer than `threshold` of its neighbours are rolls.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Rule {
+    pub neighbourhood: Neighbourhood,
+    pub threshold: usize,
+}
+
+impl Default for Rule {
+    /// The puzzle's rule: fewer than four rolls among the eight surrounding cells.
+    fn default() -> Self {
+        Rule {
+            neighbourhood: Neighbourhood::moore(),
+            threshold: 4,
         }
     }
 }
 
-fn parse<T>(reader: BufReader<T>) -> Vec<Vec<bool>>
+impl Rule {
+    fn neighbours(&self, map: &Grid, (row, column): (usize, usize)) -> usize {
+        map.neighbours(row, column, &self.neighbourhood)
+            .filter(|&(row, column)| map.get(row, column))
+            .count()
+    }
+}
+
+/// The number of rolls that can be removed from `map` under `rule` when its edges behave
+/// like `boundary`.
+pub fn count_removable(map: &Grid, rule: &Rule, boundary: Boundary) -> usize {
+    remove_all(&mut map.clone().with_boundary(boundary), rule)
+}
+
+/// A problem with the grid of rolls, located by its 1-based line number.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum GridError {
+    /// The row's length differs from the first row's.
+    RaggedRow {
+        line: usize,
+        length: usize,
+        expected: usize,
+    },
+    /// The row contains something other than `@` or `.`, at a 1-based column.
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            GridError::RaggedRow {
+                line,
+                length,
+                expected,
+            } => write!(
+                f,
+                "line {}: row has {} cells, but the first row has {}",
+                line, length, expected
+            ),
+            GridError::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} is neither a roll '@' nor empty '.'",
+                line, column, character
+            ),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+impl From<GridError> for IoError {
+    fn from(error: GridError) -> Self {
+        IoError::new(ErrorKind::InvalidData, error)
+    }
+}
+
+/// Parses a rectangular grid of `@` rolls and `.` empty cells. Trailing blank lines are
+/// ignored, anything else fails with an [`ErrorKind::InvalidData`] error.
+pub fn parse<T>(reader: BufReader<T>) -> IoResult<Grid>
 where
     T: Read,
 {
-    reader
-        .lines()
-        .flat_map(|line| line.ok())
-        .map(|line| line.chars().map(|c| c == '@').collect())
-        .collect()
+    let mut lines = reader.lines().collect::<IoResult<Vec<String>>>()?;
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let expected = lines.first().map(|line| line.chars().count());
+    let rows = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| parse_row(index + 1, line, expected.unwrap_or_default()))
+        .collect::<Result<Vec<Vec<bool>>, GridError>>()?;
+
+    Ok(Grid::from_rows(&rows))
 }
 
-fn remove_accessible(map: &mut Vec<Vec<bool>>) -> usize {
-    let mut accessible = vec![];
-    for (i, line) in map.iter().enumerate() {
-        for (j, &c) in line.iter().enumerate() {
-            if !c {
-                continue;
-            }
+fn parse_row(line_number: usize, line: &str, expected: usize) -> Result<Vec<bool>, GridError> {
+    let row = line
+        .chars()
+        .enumerate()
+        .map(|(column, c)| match c {
+            '@' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(GridError::InvalidCharacter {
+                line: line_number,
+                column: column + 1,
+                character: c,
+            }),
+        })
+        .collect::<Result<Vec<bool>, GridError>>()?;
 
-            // We already subtract the roll at [i][j]
-            let mut neighbours = -1;
-            for x in max(i, 1) - 1..min(i + 2, map.len()) {
-                for y in max(j, 1) - 1..min(j + 2, line.len()) {
-                    if  map[x][y]{
-                        neighbours += 1;
-                    }
+    if row.len() != expected {
+        return Err(GridError::RaggedRow {
+            line: line_number,
+            length: row.len(),
+            expected,
+        });
+    }
+
+    Ok(row)
+}
+
+/// Removes every roll that is accessible right now and returns how many were removed.
+pub fn remove_accessible(map: &mut Grid, rule: &Rule) -> usize {
+    let accessible: Vec<(usize, usize)> = map
+        .cells()
+        .filter(|&cell| rule.neighbours(map, cell) < rule.threshold)
+        .collect();
+
+    for &(row, column) in &accessible {
+        map.set(row, column, false);
+    }
+
+    accessible.len()
+}
+
+/// Removes rolls until none is accessible and returns how many were removed.
+pub fn remove_all(map: &mut Grid, rule: &Rule) -> usize {
+    erode(map, rule).removed()
+}
+
+/// The rolls removed in each round, where a round removes every roll accessible at its start.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Timeline {
+    pub rounds: Vec<Vec<(usize, usize)>>,
+}
+
+impl Timeline {
+    /// The number of rolls removed over all rounds.
+    pub fn removed(&self) -> usize {
+        self.rounds.iter().map(Vec::len).sum()
+    }
+}
+
+/// Removes rolls round by round until none is accessible, recording each round.
+///
+/// Every roll keeps a count of its neighbouring rolls. Removing a roll decrements the counts
+/// around it, and a roll whose count just dropped below the threshold joins the next round, so
+/// each roll is queued at most once and the work is proportional to cells plus removals.
+pub fn erode(map: &mut Grid, rule: &Rule) -> Timeline {
+    // Removing a roll affects the rolls that count it as a neighbour
+    let affected = rule.neighbourhood.reversed();
+    let width = map.width();
+    let mut neighbours = vec![0_usize; width * map.height()];
+    let mut round = vec![];
+
+    for cell in map.cells().collect::<Vec<_>>() {
+        let count = rule.neighbours(map, cell);
+        neighbours[cell.0 * width + cell.1] = count;
+        if count < rule.threshold {
+            round.push(cell);
+        }
+    }
+
+    let mut timeline = Timeline::default();
+
+    while !round.is_empty() {
+        let mut next = vec![];
+
+        for &(row, column) in &round {
+            map.set(row, column, false);
+
+            for (row, column) in map.neighbours(row, column, &affected) {
+                if !map.get(row, column) {
+                    continue;
+                }
+
+                let count = &mut neighbours[row * width + column];
+                *count -= 1;
+                // Rolls that were already accessible are queued, so only enqueue on the crossing
+                if *count + 1 == rule.threshold {
+                    next.push((row, column));
                 }
             }
+        }
 
-            if neighbours < 4 {
-                accessible.push((i, j));
+        timeline.rounds.push(round);
+        round = next;
+    }
+
+    timeline
+}
+
+/// When a cell lost its roll.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Depth {
+    /// The cell never held a roll.
+    Empty,
+    /// The roll was removed in this round, counting from 1.
+    Removed(usize),
+    /// The roll is never accessible.
+    Survives,
+}
+
+/// The round in which every roll of a grid is removed, peeling it like an onion.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct DepthMap {
+    width: usize,
+    height: usize,
+    depths: Vec<Depth>,
+}
+
+impl DepthMap {
+    /// Erodes a copy of `map` under `rule` and records the round each roll goes in.
+    pub fn new(map: &Grid, rule: &Rule) -> Self {
+        let timeline = erode(&mut map.clone(), rule);
+        DepthMap::from_timeline(map, &timeline)
+    }
+
+    /// The depths of `map`'s rolls given the `timeline` of its erosion.
+    pub fn from_timeline(map: &Grid, timeline: &Timeline) -> Self {
+        let width = map.width();
+        let mut depths = vec![Depth::Empty; width * map.height()];
+
+        for (i, j) in map.cells() {
+            depths[i * width + j] = Depth::Survives;
+        }
+
+        for (round, cells) in timeline.rounds.iter().enumerate() {
+            for &(i, j) in cells {
+                depths[i * width + j] = Depth::Removed(round + 1);
             }
         }
+
+        DepthMap {
+            width,
+            height: map.height(),
+            depths,
+        }
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> Depth {
+        assert!(row < self.height && column < self.width);
+        self.depths[row * self.width + column]
+    }
+
+    /// The last round in which a roll was removed, `None` if none ever was.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.depths
+            .iter()
+            .filter_map(|depth| match depth {
+                Depth::Removed(round) => Some(*round),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// The cells whose roll was removed in `round`, row by row.
+    pub fn removed_in(&self, round: usize) -> Vec<(usize, usize)> {
+        self.cells_where(Depth::Removed(round))
+    }
+
+    /// The cells whose roll is never removed, row by row.
+    pub fn survivors(&self) -> Vec<(usize, usize)> {
+        self.cells_where(Depth::Survives)
     }
 
-    let res = accessible.len();
+    fn cells_where(&self, wanted: Depth) -> Vec<(usize, usize)> {
+        self.depths
+            .iter()
+            .enumerate()
+            .filter(|(_, depth)| **depth == wanted)
+            .map(|(index, _)| (index / self.width, index % self.width))
+            .collect()
+    }
+}
+
+impl Display for DepthMap {
+    /// One line per row with the round of each removed roll, `.` for empty cells and `#` for
+    /// surviving rolls, right-aligned and separated by spaces.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let width = self.max_depth().map_or(1, |depth| depth.to_string().len());
+
+        for row in self.depths.chunks(self.width.max(1)) {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|depth| match depth {
+                    Depth::Empty => format!("{:>width$}", "."),
+                    Depth::Removed(round) => format!("{:>width$}", round),
+                    Depth::Survives => format!("{:>width$}", "#"),
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders `map` before any removal and after every round of `timeline` as text.
+///
+/// Rolls are `@`, empty cells `.` and rolls removed in the round just finished `x`.
+pub fn render_frames(map: &Grid, timeline: &Timeline) -> Vec<String> {
+    frames(map, timeline)
+        .map(|(frame, removed)| {
+            let mut text = String::with_capacity((frame.width() + 1) * frame.height());
+            for i in 0..frame.height() {
+                for j in 0..frame.width() {
+                    text.push(match (frame.get(i, j), removed.get(i, j)) {
+                        (true, _) => '@',
+                        (false, true) => 'x',
+                        (false, false) => '.',
+                    });
+                }
+                text.push('\n');
+            }
+            text
+        })
+        .collect()
+}
+
+/// Writes the frames of [`render_frames`] as binary PGM images named `frame_0000.pgm` and up
+/// into `directory`, with every cell drawn as a `scale` by `scale` square.
+///
+/// Rolls are black, empty cells white and rolls removed in the round just finished grey.
+pub fn write_pgm_frames(
+    directory: &Path,
+    map: &Grid,
+    timeline: &Timeline,
+    scale: usize,
+) -> IoResult<Vec<PathBuf>> {
+    fs::create_dir_all(directory)?;
 
-    for (i, j) in accessible {
-        map[i][j] = false;
+    let mut paths = vec![];
+
+    for (index, (frame, removed)) in frames(map, timeline).enumerate() {
+        let path = directory.join(format!("frame_{:04}.pgm", index));
+        let mut out = BufWriter::new(fs::File::create(&path)?);
+
+        writeln!(
+            out,
+            "P5\n{} {}\n255",
+            frame.width() * scale,
+            frame.height() * scale
+        )?;
+
+        for i in 0..frame.height() {
+            let row: Vec<u8> = (0..frame.width())
+                .flat_map(|j| {
+                    let shade = match (frame.get(i, j), removed.get(i, j)) {
+                        (true, _) => 0,
+                        (false, true) => 128,
+                        (false, false) => 255,
+                    };
+                    std::iter::repeat_n(shade, scale)
+                })
+                .collect();
+            for _ in 0..scale {
+                out.write_all(&row)?;
+            }
+        }
+
+        out.flush()?;
+        paths.push(path);
     }
 
-    res
+    Ok(paths)
+}
+
+/// The grid before any removal and after each round, with the rolls removed in that round.
+fn frames<'a>(map: &Grid, timeline: &'a Timeline) -> impl Iterator<Item = (Grid, Grid)> + 'a {
+    let initial = (map.clone(), Grid::new(map.width(), map.height()));
+
+    std::iter::once(initial.clone()).chain(timeline.rounds.iter().scan(
+        initial.0,
+        |frame, round| {
+            let mut removed = Grid::new(frame.width(), frame.height());
+            for &(i, j) in round {
+                frame.set(i, j, false);
+                removed.set(i, j, true);
+            }
+            Some((frame.clone(), removed))
+        },
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::BufReader;
+    use std::io::ErrorKind;
+
+    use crate::day04::Depth;
+    use crate::day04::DepthMap;
+    use crate::day04::GridError;
+    use crate::day04::Rule;
+    use crate::day04::count_removable;
+    use crate::day04::erode;
+    use crate::day04::parse;
+    use crate::day04::remove_accessible;
+    use crate::day04::remove_all;
+    use crate::day04::render_frames;
+    use crate::day04::write_pgm_frames;
+    use crate::grid::Boundary;
+    use crate::grid::Grid;
+    use crate::grid::Neighbourhood;
+    use crate::prop;
+    use crate::rng::Rng;
+
+    /// The original round-by-round rescan, kept as a reference for `erode`.
+    fn erode_rescanning(map: &mut Grid, rule: &Rule) -> Vec<Vec<(usize, usize)>> {
+        let mut rounds = vec![];
+
+        loop {
+            let before = map.clone();
+            let removed = remove_accessible(map, rule);
+            if removed > 0 {
+                rounds.push(before.cells().filter(|&(i, j)| !map.get(i, j)).collect());
+            } else {
+                return rounds;
+            }
+        }
+    }
+
+    /// A reproducible grid of the given size, with roughly seven in ten cells holding a roll.
+    fn random_grid(seed: u64, width: usize, height: usize) -> Grid {
+        let mut rng = Rng::new(seed);
+        let rows: Vec<Vec<bool>> = (0..height)
+            .map(|_| (0..width).map(|_| rng.chance(7, 10)).collect())
+            .collect();
+        Grid::from_rows(&rows)
+    }
+
+    #[test]
+    fn erode_matches_rescanning() {
+        let rules = [
+            Rule::default(),
+            Rule {
+                neighbourhood: Neighbourhood::von_neumann(),
+                threshold: 3,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::radius(2),
+                threshold: 12,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::custom([(0, 1), (0, -1), (2, 0)]),
+                threshold: 2,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::moore(),
+                threshold: 0,
+            },
+        ];
+
+        let boundaries = [Boundary::Bounded, Boundary::Torus];
+
+        for (seed, rule, boundary) in (1..20_u64).flat_map(|seed| {
+            rules
+                .iter()
+                .flat_map(move |rule| boundaries.map(|boundary| (seed, rule, boundary)))
+        }) {
+            // Arrange
+            let mut map = random_grid(seed, 23, 17).with_boundary(boundary);
+            let mut reference = map.clone();
+
+            // Act
+            let mut timeline = erode(&mut map, rule);
+            let expected = erode_rescanning(&mut reference, rule);
+
+            // Assert
+            timeline
+                .rounds
+                .iter_mut()
+                .for_each(|round| round.sort_unstable());
+            assert_eq!(
+                expected, timeline.rounds,
+                "seed {seed}, {rule:?}, {boundary:?}"
+            );
+            assert_eq!(reference, map, "seed {seed}, {rule:?}, {boundary:?}");
+        }
+    }
+
+    #[test]
+    fn render_frames_per_round() {
+        // Arrange
+        let map = parse(BufReader::new(["@@@", "@@@"].join("\n").as_bytes())).unwrap();
+        let rule = Rule {
+            neighbourhood: Neighbourhood::von_neumann(),
+            threshold: 3,
+        };
+        let timeline = erode(&mut map.clone(), &rule);
+
+        // Act
+        let frames = render_frames(&map, &timeline);
+
+        // Assert
+        assert_eq!(2, timeline.rounds.len());
+        assert_eq!(vec!["@@@\n@@@\n", "x@x\nx@x\n", ".x.\n.x.\n"], frames);
+    }
+
+    #[test]
+    fn write_pgm_frames_headers() {
+        // Arrange
+        let map = parse(BufReader::new(["@@@", "@@@"].join("\n").as_bytes())).unwrap();
+        let timeline = erode(&mut map.clone(), &Rule::default());
+        let directory = std::env::temp_dir().join(format!("aoc25-day04-{}", std::process::id()));
+
+        // Act
+        let paths = write_pgm_frames(&directory, &map, &timeline, 2).unwrap();
+
+        // Assert
+        assert_eq!(3, paths.len());
+        let image = std::fs::read(&paths[1]).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert!(image.starts_with(b"P5\n6 4\n255\n"));
+        assert_eq!(b"P5\n6 4\n255\n".len() + 6 * 4, image.len());
+        assert!(image.ends_with(&[128, 128, 0, 0, 128, 128]));
+    }
+
+    #[test]
+    fn count_removable_boundaries() {
+        // Arrange
+        let map = parse(BufReader::new(["@@@", "@@@", "@@@"].join("\n").as_bytes())).unwrap();
+
+        // Act / Assert
+        assert_eq!(
+            9,
+            count_removable(&map, &Rule::default(), Boundary::Bounded)
+        );
+        assert_eq!(0, count_removable(&map, &Rule::default(), Boundary::Torus));
+    }
+
+    #[test]
+    fn parse_rejects_ragged_rows() {
+        // Arrange
+        let input = ["@@.", "@.", "..."].join("\n");
+
+        // Act
+        let result = parse(BufReader::new(input.as_bytes()));
+
+        // Assert
+        let error = result.unwrap_err();
+        assert_eq!(
+            Some(&GridError::RaggedRow {
+                line: 2,
+                length: 2,
+                expected: 3
+            }),
+            error.get_ref().and_then(|e| e.downcast_ref::<GridError>())
+        );
+        assert_eq!(
+            "line 2: row has 2 cells, but the first row has 3",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_characters() {
+        // Arrange
+        let input = ["@@.", "@#.", "..."].join("\n");
+
+        // Act
+        let result = parse(BufReader::new(input.as_bytes()));
+
+        // Assert
+        assert_eq!(
+            "line 2, column 2: '#' is neither a roll '@' nor empty '.'",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn parse_ignores_trailing_blank_lines() {
+        // Act
+        let grid = parse(BufReader::new("@.\n.@\n\n".as_bytes())).unwrap();
+
+        // Assert
+        assert_eq!((2, 2), (grid.width(), grid.height()));
+    }
 
-    use crate::day04::part1_impl;
-    use crate::day04::part2_impl;
+    #[test]
+    fn parse_fails_on_lines_that_are_not_utf8() {
+        // Act
+        let result = parse(BufReader::new(&b"@.\n\xff.\n.@\n"[..]));
+
+        // Assert
+        assert_eq!(ErrorKind::InvalidData, result.unwrap_err().kind());
+    }
 
     #[test]
-    fn part1_example() {
+    fn depth_map_peels_layers() {
         // Arrange
-        let input = vec![
-            "..@@.@@@@.",
-            "@@@.@.@.@@",
-            "@@@@@.@.@@",
-            "@.@@@@..@.",
-            "@@.@@@@.@@",
-            ".@@@@@@@.@",
-            ".@.@.@.@@@",
-            "@.@@@.@@@@",
-            ".@@@@@@@@.",
-            "@.@.@@@.@.",
-        ]
-        .join("\n");
+        let map = parse(BufReader::new(
+            [".@@@@@", "@@@@@@", "@@@@@@", "@@@@@@"]
+                .join("\n")
+                .as_bytes(),
+        ))
+        .unwrap();
+        let rule = Rule {
+            neighbourhood: Neighbourhood::von_neumann(),
+            threshold: 3,
+        };
 
         // Act
-        let result = part1_impl(BufReader::new(input.as_bytes()));
+        let depths = DepthMap::new(&map, &rule);
 
         // Assert
-        assert_eq!(13, result);
+        assert_eq!(Depth::Empty, depths.get(0, 0));
+        assert_eq!(Depth::Removed(1), depths.get(0, 1));
+        assert_eq!(Depth::Removed(4), depths.get(1, 3));
+        assert_eq!(Some(4), depths.max_depth());
+        assert_eq!(vec![(1, 3), (2, 2), (2, 3)], depths.removed_in(4));
+        assert_eq!(
+            ". 1 2 3 2 1\n1 2 3 4 3 2\n2 3 4 4 3 2\n1 2 3 3 2 1\n",
+            depths.to_string()
+        );
+        assert!(depths.survivors().is_empty());
     }
+
     #[test]
-    fn part2_example() {
+    fn depth_map_survivors() {
         // Arrange
-        let input = vec![
-            "..@@.@@@@.",
-            "@@@.@.@.@@",
-            "@@@@@.@.@@",
-            "@.@@@@..@.",
-            "@@.@@@@.@@",
-            ".@@@@@@@.@",
-            ".@.@.@.@@@",
-            "@.@@@.@@@@",
-            ".@@@@@@@@.",
-            "@.@.@@@.@.",
-        ]
-        .join("\n");
+        let map = parse(BufReader::new(["@@@", "@@@", "@@@"].join("\n").as_bytes()))
+            .unwrap()
+            .with_boundary(Boundary::Torus);
 
         // Act
-        let result = part2_impl(BufReader::new(input.as_bytes()));
+        let depths = DepthMap::new(&map, &Rule::default());
 
         // Assert
-        assert_eq!(43, result);
+        assert_eq!(None, depths.max_depth());
+        assert_eq!(9, depths.survivors().len());
+        assert_eq!("# # #\n# # #\n# # #\n", depths.to_string());
+    }
+
+    #[test]
+    fn depth_map_matches_timeline() {
+        // Arrange
+        let map = random_grid(7, 40, 40);
+        let timeline = erode(&mut map.clone(), &Rule::default());
+
+        // Act
+        let depths = DepthMap::new(&map, &Rule::default());
+
+        // Assert
+        assert_eq!(Some(timeline.rounds.len()), depths.max_depth());
+        for (round, cells) in timeline.rounds.iter().enumerate() {
+            let mut cells = cells.clone();
+            cells.sort_unstable();
+            assert_eq!(cells, depths.removed_in(round + 1));
+        }
+    }
+
+    #[test]
+    fn removal_never_increases_rolls() {
+        prop::check(
+            300,
+            |rng| {
+                let width = rng.range(0, 12) as usize;
+                rng.vec(0, 12, |rng| rng.vec(width, width, |rng| rng.chance(7, 10)))
+            },
+            |rows: &Vec<Vec<bool>>| {
+                let rule = Rule::default();
+                let mut map = Grid::from_rows(rows);
+                let initial = map.count_ones();
+
+                let mut rolls = initial;
+                loop {
+                    let removed = remove_accessible(&mut map, &rule);
+                    if removed > rolls || map.count_ones() != rolls - removed {
+                        return false;
+                    }
+                    rolls -= removed;
+                    if removed == 0 {
+                        break;
+                    }
+                }
+
+                initial - rolls == remove_all(&mut Grid::from_rows(rows), &rule)
+            },
+        );
     }
 }
@@ -1,223 +1,939 @@
 use std::{
-    cmp::max,
-    fs::OpenOptions,
-    io::{BufRead, BufReader, Read, Result as IoResult},
+    cmp::{Reverse, max},
+    collections::BTreeMap,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{BufRead, BufReader, Error as IoError, ErrorKind, Read, Result as IoResult, Write},
+    path::Path,
 };
 
-pub fn day05(file_path: &str) -> IoResult<()> {
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part1_impl(reader);
-    println!("Day 05, Part 1: {}", result);
+use crate::{
+    days::Answers,
+    input,
+    interval_set::{IntervalSet, Range},
+    interval_tree::IntervalTree,
+};
 
-    let file = OpenOptions::new().read(true).open(file_path)?;
-    let reader = BufReader::new(file);
-    let result = part2_impl(reader);
-    println!("Day 05, Part 2: {}", result);
+pub fn day05(file_path: &Path) -> IoResult<()> {
+    let answers = solve(&input::read(file_path)?)?;
+    println!("Day 05, Part 1: {}", answers.part1);
+    println!("Day 05, Part 2: {}", answers.part2);
 
     Result::Ok(())
 }
 
-fn part1_impl<T>(mut reader: BufReader<T>) -> usize
+/// Solves both parts of an already loaded input.
+pub fn solve(input: &str) -> IoResult<Answers> {
+    Ok(Answers::new(
+        part1_impl(BufReader::new(input.as_bytes()))?,
+        part2_impl(BufReader::new(input.as_bytes()))?,
+    ))
+}
+
+fn part1_impl<T>(reader: BufReader<T>) -> IoResult<usize>
 where
     T: Read,
 {
-    let ranges = parse_ranges(&mut reader);
-    parse_ingredients(reader)
-        .filter(|ingredient| ranges.contains(*ingredient))
-        .count()
+    let database = parse(reader)?;
+    let ranges = database.fresh();
+    Ok(database
+        .ingredients
+        .iter()
+        .filter(|ingredient| ranges.contains(**ingredient))
+        .count())
 }
 
-fn part2_impl<T>(mut reader: BufReader<T>) -> usize
+fn part2_impl<T>(reader: BufReader<T>) -> IoResult<u128>
 where
     T: Read,
 {
-    let ranges = parse_ranges(&mut reader);
-    ranges.iter().map(Range::size).sum()
+    let database = parse(reader)?;
+    Ok(database.fresh().coverage())
+}
+
+/// The two sections of an input: the fresh ranges, then the available ingredients.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Database {
+    pub ranges: Vec<SourceRange>,
+    pub ingredients: Vec<u64>,
 }
 
-fn parse_ingredients<T>(
-    reader: BufReader<T>,
-) -> std::iter::FilterMap<
-    std::io::Lines<BufReader<T>>,
-    fn(Result<String, std::io::Error>) -> Option<usize>,
->
+impl Database {
+    /// The fresh IDs, with the ranges merged.
+    pub fn fresh(&self) -> IntervalSet {
+        self.ranges.iter().map(|source| source.range).collect()
+    }
+}
+
+/// A problem with the layout of an input, located by its 1-based line number.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum DatabaseError {
+    /// An ingredient ID among the ranges, most likely after a missing blank line.
+    MissingSeparator { line: usize },
+    /// A line in the ranges section that is not `start-end` with `start <= end`.
+    InvalidRange { line: usize, text: String },
+    /// A line in the ingredients section that is not an ID.
+    InvalidIngredient { line: usize, text: String },
+    /// Another blank line and more content after the ingredients.
+    ExtraSection { line: usize },
+    /// The input ended before the named section had any content.
+    MissingSection { section: &'static str },
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DatabaseError::MissingSeparator { line } => write!(
+                f,
+                "line {}: ingredient ID among the ranges, is the blank line before the ingredients missing?",
+                line
+            ),
+            DatabaseError::InvalidRange { line, text } => {
+                write!(f, "line {}: {:?} is not a range start-end", line, text)
+            }
+            DatabaseError::InvalidIngredient { line, text } => {
+                write!(f, "line {}: {:?} is not an ingredient ID", line, text)
+            }
+            DatabaseError::ExtraSection { line } => {
+                write!(f, "line {}: unexpected section after the ingredients", line)
+            }
+            DatabaseError::MissingSection { section } => {
+                write!(f, "the input has no {} section", section)
+            }
+        }
+    }
+}
+
+impl Error for DatabaseError {}
+
+impl From<DatabaseError> for IoError {
+    fn from(error: DatabaseError) -> Self {
+        IoError::new(ErrorKind::InvalidData, error)
+    }
+}
+
+/// Parses the ranges section and the ingredients section of an input.
+///
+/// The sections are separated by one or more blank lines, where lines holding only whitespace
+/// count as blank, and surrounding whitespace such as the `\r` of CRLF line endings is ignored.
+/// Both sections must be present and non-empty.
+pub fn parse<T>(reader: BufReader<T>) -> IoResult<Database>
 where
     T: Read,
 {
-    fn parse_usize(s: IoResult<String>) -> Option<usize> {
-        s.ok()?.parse().ok()
+    let mut database = Database {
+        ranges: vec![],
+        ingredients: vec![],
+    };
+    let mut in_ingredients = false;
+    let mut after_blank = false;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let text = line.trim();
+        let line_number = index + 1;
+
+        if text.is_empty() {
+            after_blank = true;
+            continue;
+        }
+
+        // Leading blank lines and those right after the separator do not start a section
+        if after_blank && !database.ranges.is_empty() {
+            if !in_ingredients {
+                in_ingredients = true;
+            } else if !database.ingredients.is_empty() {
+                return Err(DatabaseError::ExtraSection { line: line_number }.into());
+            }
+        }
+        after_blank = false;
+
+        if in_ingredients {
+            let ingredient = text.parse().map_err(|_| DatabaseError::InvalidIngredient {
+                line: line_number,
+                text: text.to_string(),
+            })?;
+            database.ingredients.push(ingredient);
+        } else {
+            database.ranges.push(parse_source_range(line_number, text)?);
+        }
+    }
+
+    if database.ranges.is_empty() {
+        return Err(DatabaseError::MissingSection { section: "ranges" }.into());
     }
-    reader.lines().filter_map(parse_usize)
+    if database.ingredients.is_empty() {
+        return Err(DatabaseError::MissingSection {
+            section: "ingredients",
+        }
+        .into());
+    }
+
+    Ok(database)
 }
 
-fn parse_ranges<T>(reader: &mut BufReader<T>) -> Vec<Range>
+/// Parses only the ranges section of an input, for the reports that never look at the
+/// ingredients. Reading stops at the blank line after the ranges, which must be non-empty.
+pub fn parse_ranges<T>(reader: BufReader<T>) -> IoResult<Vec<SourceRange>>
 where
     T: Read,
 {
-    let ranges: Vec<Range> = reader
-        .lines()
-        .filter_map(IoResult::ok)
-        .take_while(|line| !line.is_empty())
-        .filter_map(parse_range)
+    let mut ranges = vec![];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let text = line.trim();
+
+        if text.is_empty() {
+            if ranges.is_empty() {
+                continue;
+            }
+            break;
+        }
+
+        ranges.push(parse_source_range(index + 1, text)?);
+    }
+
+    if ranges.is_empty() {
+        return Err(DatabaseError::MissingSection { section: "ranges" }.into());
+    }
+
+    Ok(ranges)
+}
+
+/// Parses a line of the ranges section, telling a stray ingredient ID from a broken range.
+fn parse_source_range(line: usize, text: &str) -> Result<SourceRange, DatabaseError> {
+    if let Some(range) = parse_range(text.to_string()) {
+        Ok(SourceRange { line, range })
+    } else if text.parse::<u64>().is_ok() {
+        Err(DatabaseError::MissingSeparator { line })
+    } else {
+        Err(DatabaseError::InvalidRange {
+            line,
+            text: text.to_string(),
+        })
+    }
+}
+
+/// The spoiled IDs within some bounds, as the gaps between the merged fresh ranges.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GapReport {
+    pub gaps: Vec<Range>,
+    /// The number of IDs over all gaps.
+    pub total: u128,
+    /// The first of the largest gaps, `None` if everything is fresh.
+    pub largest: Option<Range>,
+}
+
+impl GapReport {
+    pub fn new(ranges: &IntervalSet, bounds: Range) -> Self {
+        let gaps = ranges.complement(bounds).ranges().to_vec();
+        let total = gaps.iter().map(Range::size).sum();
+        let largest = gaps.iter().rev().max_by_key(|gap| gap.size()).copied();
+
+        GapReport {
+            gaps,
+            total,
+            largest,
+        }
+    }
+}
+
+impl Display for GapReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for gap in &self.gaps {
+            writeln!(f, "{}-{} ({} IDs)", gap.start, gap.end, gap.size())?;
+        }
+        writeln!(f, "{} spoiled IDs in {} gaps", self.total, self.gaps.len())?;
+        match self.largest {
+            Some(gap) => writeln!(
+                f,
+                "largest gap: {}-{} ({} IDs)",
+                gap.start,
+                gap.end,
+                gap.size()
+            ),
+            None => writeln!(f, "no gaps"),
+        }
+    }
+}
+
+/// Reads the fresh ranges of an input and reports the gaps between them within `bounds`.
+pub fn gap_report<T>(reader: BufReader<T>, bounds: Range) -> IoResult<GapReport>
+where
+    T: Read,
+{
+    let fresh = parse_ranges(reader)?
+        .into_iter()
+        .map(|source| source.range)
         .collect();
+    Ok(GapReport::new(&fresh, bounds))
+}
 
-    merge_ranges(ranges)
+/// Fresh ranges that change over time, kept merged in a balanced tree keyed by their start.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Inventory {
+    /// Disjoint, non-adjacent ranges as `start -> end`.
+    ranges: BTreeMap<u64, u64>,
 }
 
-fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
-    if ranges.len() <= 0 {
-        return ranges;
+impl Inventory {
+    pub fn new() -> Self {
+        Inventory::default()
+    }
+
+    /// Marks every ID of `range` fresh.
+    pub fn add(&mut self, range: Range) {
+        if range.is_empty() {
+            return;
+        }
+
+        let Range { mut start, mut end } = range;
+
+        // A range starting before ours may overlap or touch it
+        if let Some((&before_start, &before_end)) = self.ranges.range(..=start).next_back()
+            && before_end.saturating_add(1) >= start
+        {
+            start = before_start;
+            end = max(end, before_end);
+        }
+
+        let touched: Vec<(u64, u64)> = self
+            .ranges
+            .range(start..=end.saturating_add(1))
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (touched_start, touched_end) in touched {
+            self.ranges.remove(&touched_start);
+            end = max(end, touched_end);
+        }
+
+        self.ranges.insert(start, end);
     }
 
-    ranges.sort_unstable();
+    /// Marks every ID of `range` spoiled.
+    pub fn remove(&mut self, range: Range) {
+        if range.is_empty() {
+            return;
+        }
 
-    ranges.iter().fold(vec![], |mut merged_ranges, range| {
-        // The ranges are sorted, so we only need to look at the last one
-        match merged_ranges.last_mut() {
-            Some(current) if current.end + 1 >= range.start => {
-                // if the last range itersects or is adjacent, we can merge them
-                current.end = max(current.end, range.end)
+        // A range starting before ours keeps its head, and its tail if it reaches past ours
+        if let Some((&before_start, &before_end)) = self.ranges.range(..range.start).next_back()
+            && before_end >= range.start
+        {
+            self.ranges.insert(before_start, range.start - 1);
+            if before_end > range.end {
+                self.ranges.insert(range.end + 1, before_end);
             }
-            // otherwise, make a new range
-            _ => merged_ranges.push(*range),
-        };
-        merged_ranges
-    })
-}
+        }
 
-fn parse_range(s: String) -> Option<Range> {
-    let dash = s.find('-')?;
+        let inside: Vec<(u64, u64)> = self
+            .ranges
+            .range(range.start..=range.end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (inside_start, inside_end) in inside {
+            self.ranges.remove(&inside_start);
+            if inside_end > range.end {
+                self.ranges.insert(range.end + 1, inside_end);
+            }
+        }
+    }
 
-    Some(Range {
-        start: s[..dash].parse().ok()?,
-        end: s[(dash + 1)..].parse().ok()?,
-    })
+    pub fn is_fresh(&self, id: u64) -> bool {
+        self.ranges
+            .range(..=id)
+            .next_back()
+            .is_some_and(|(_, &end)| id <= end)
+    }
+
+    /// The fresh ranges, sorted and merged.
+    pub fn ranges(&self) -> impl Iterator<Item = Range> + '_ {
+        self.ranges
+            .iter()
+            .map(|(&start, &end)| Range { start, end })
+    }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
-struct Range {
-    start: usize,
-    end: usize,
+/// One line of an inventory stream.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Command {
+    /// `+start-end` marks a range fresh.
+    Add(Range),
+    /// `-start-end` marks a range spoiled.
+    Remove(Range),
+    /// `?id` asks whether an ID is fresh.
+    Query(u64),
 }
 
-impl Range {
-    fn contains(&self, ingredient: usize) -> bool {
-        self.start <= ingredient && ingredient <= self.end
+fn parse_command(line: &str) -> Option<Command> {
+    let line = line.trim();
+    if let Some(range) = line.strip_prefix('+') {
+        Some(Command::Add(parse_range(range.to_string())?))
+    } else if let Some(range) = line.strip_prefix('-') {
+        Some(Command::Remove(parse_range(range.to_string())?))
+    } else if let Some(id) = line.strip_prefix('?') {
+        Some(Command::Query(id.parse().ok()?))
+    } else {
+        None
     }
+}
+
+/// Applies a stream of commands to an empty inventory, answering every query as soon as it
+/// is read with a line `id: fresh` or `id: spoiled`. Blank lines are skipped.
+///
+/// `out` is flushed after every answer, so a buffered writer does not hold answers back
+/// until the stream ends.
+pub fn run_commands<T, W>(reader: BufReader<T>, out: &mut W) -> IoResult<Inventory>
+where
+    T: Read,
+    W: Write,
+{
+    let mut inventory = Inventory::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
 
-    fn size(&self) -> usize {
-        self.end - self.start + 1
+        match parse_command(&line) {
+            Some(Command::Add(range)) => inventory.add(range),
+            Some(Command::Remove(range)) => inventory.remove(range),
+            Some(Command::Query(id)) => {
+                let state = if inventory.is_fresh(id) {
+                    "fresh"
+                } else {
+                    "spoiled"
+                };
+                writeln!(out, "{}: {}", id, state)?;
+                out.flush()?;
+            }
+            None => {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: {:?} is not a command", index + 1, line),
+                ));
+            }
+        }
     }
+
+    Ok(inventory)
 }
 
-trait Ranges {
-    fn contains(&self, ingredient: usize) -> bool;
+/// Reads every ingredient and finds, for each, the input line of a range making it fresh.
+pub fn check_ingredients<T>(reader: BufReader<T>) -> IoResult<Vec<(u64, Option<usize>)>>
+where
+    T: Read,
+{
+    let database = parse(reader)?;
+    let fresh = fresh_by(&database.ranges, &database.ingredients);
+
+    Ok(database.ingredients.into_iter().zip(fresh).collect())
 }
 
-impl Ranges for Vec<Range> {
-    fn contains(&self, ingredient: usize) -> bool {
-        let insertion_index = self.binary_search_by_key(&ingredient, |r| r.start);
-        match insertion_index {
-            Ok(i) if self[i].contains(ingredient) => true,
-            Ok(i) if 0 < i && self[i - 1].contains(ingredient) => true,
-            Err(i) if i < self.len() && self[i].contains(ingredient) => true,
-            Err(i) if i > 0 && self[i - 1].contains(ingredient) => true,
-            _ => false,
+/// For every ingredient, the line of a range containing it, or `None` if it is spoiled.
+///
+/// The ingredients are sorted and swept in one pass against the ranges sorted by start:
+/// among the ranges started so far, the one reaching furthest contains an ingredient whenever
+/// any of them does. Ties go to the earlier line. The results are in the order of `ingredients`.
+pub fn fresh_by(ranges: &[SourceRange], ingredients: &[u64]) -> Vec<Option<usize>> {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_unstable_by_key(|source| (source.range.start, source.line));
+    let mut ranges = ranges.into_iter().peekable();
+
+    let mut order: Vec<usize> = (0..ingredients.len()).collect();
+    order.sort_unstable_by_key(|&index| ingredients[index]);
+
+    let mut fresh = vec![None; ingredients.len()];
+    let mut furthest: Option<SourceRange> = None;
+
+    for index in order {
+        let ingredient = ingredients[index];
+
+        while let Some(source) = ranges.next_if(|source| source.range.start <= ingredient) {
+            let reaches_further = furthest.is_none_or(|best| {
+                (source.range.end, Reverse(source.line)) > (best.range.end, Reverse(best.line))
+            });
+            if reaches_further {
+                furthest = Some(source);
+            }
         }
+
+        fresh[index] = furthest
+            .filter(|best| best.range.contains(ingredient))
+            .map(|best| best.line);
     }
+
+    fresh
+}
+
+/// Keeps the ranges of an input unmerged, each with its line, so we can still tell which
+/// lines make an ingredient fresh and how many of them overlap.
+pub fn parse_provenance<T>(reader: BufReader<T>) -> IoResult<IntervalTree<usize>>
+where
+    T: Read,
+{
+    Ok(parse_ranges(reader)?
+        .into_iter()
+        .map(|source| (source.range, source.line))
+        .collect())
+}
+
+/// A range together with the 1-based input line it was read from.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SourceRange {
+    pub line: usize,
+    pub range: Range,
+}
+
+/// Parses `start-end`, rejecting reversed ranges.
+fn parse_range(s: String) -> Option<Range> {
+    let dash = s.find('-')?;
+
+    let range = Range {
+        start: s[..dash].parse().ok()?,
+        end: s[(dash + 1)..].parse().ok()?,
+    };
+
+    (!range.is_empty()).then_some(range)
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::BufReader;
-
-    use crate::day05::Range;
+    use std::io::ErrorKind;
+    use std::io::Result as IoResult;
+    use std::io::Write;
+
+    use crate::day05::DatabaseError;
+    use crate::day05::Inventory;
+    use crate::day05::SourceRange;
+    use crate::day05::check_ingredients;
+    use crate::day05::fresh_by;
+    use crate::day05::gap_report;
+    use crate::day05::parse;
+    use crate::day05::parse_provenance;
     use crate::day05::parse_range;
-    use crate::day05::part1_impl;
+    use crate::day05::parse_ranges;
     use crate::day05::part2_impl;
+    use crate::day05::run_commands;
+    use crate::interval_set::IntervalSet;
+    use crate::interval_set::Range;
+    use crate::rng::Rng;
+
+    #[test]
+    fn parse_range_example() {
+        let result = parse_range("123-456".to_string());
 
-    use crate::day05::Ranges;
+        assert_eq!(
+            Some(Range {
+                start: 123,
+                end: 456
+            }),
+            result
+        )
+    }
 
     #[test]
-    fn part1_example() {
+    fn check_ingredients_example() {
         // Arrange
-        let input = vec![
+        let input = [
             "3-5", "10-14", "16-20", "12-18", "", "1", "5", "8", "11", "17", "32",
         ]
         .join("\n");
 
         // Act
-        let res = part1_impl(BufReader::new(input.as_bytes()));
+        let result = check_ingredients(BufReader::new(input.as_bytes())).unwrap();
 
         // Assert
-        assert_eq!(3, res)
+        assert_eq!(
+            vec![
+                (1, None),
+                (5, Some(1)),
+                (8, None),
+                (11, Some(2)),
+                (17, Some(3)),
+                (32, None)
+            ],
+            result
+        );
     }
 
     #[test]
-    fn part2_example() {
+    fn fresh_by_matches_linear_search() {
         // Arrange
-        let input = vec![
+        let ranges: Vec<SourceRange> = [(5, 9), (0, 2), (4, 6), (20, 30), (7, 12), (25, 26)]
+            .iter()
+            .enumerate()
+            .map(|(index, &(start, end))| SourceRange {
+                line: index + 1,
+                range: Range { start, end },
+            })
+            .collect();
+        let ingredients: Vec<u64> = (0..40).rev().chain(0..40).collect();
+
+        // Act
+        let result = fresh_by(&ranges, &ingredients);
+
+        // Assert
+        for (ingredient, fresh) in ingredients.iter().zip(result) {
+            match fresh {
+                Some(line) => assert!(ranges[line - 1].range.contains(*ingredient)),
+                None => assert!(ranges.iter().all(|r| !r.range.contains(*ingredient))),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_provenance_example() {
+        // Arrange
+        let input = [
             "3-5", "10-14", "16-20", "12-18", "", "1", "5", "8", "11", "17", "32",
         ]
         .join("\n");
 
         // Act
-        let res = part2_impl(BufReader::new(input.as_bytes()));
+        let provenance = parse_provenance(BufReader::new(input.as_bytes())).unwrap();
 
         // Assert
-        assert_eq!(14, res)
+        let lines: Vec<usize> = provenance
+            .containing(13)
+            .iter()
+            .map(|(_, line)| *line)
+            .collect();
+        assert_eq!(vec![2, 4], lines);
+        assert_eq!(2, provenance.overlaps(17));
+        assert_eq!(0, provenance.overlaps(8));
+        assert_eq!(4, provenance.len());
     }
 
     #[test]
-    fn parse_range_example() {
-        let result = parse_range("123-456".to_string());
+    fn parse_range_rejects_reversed() {
+        assert_eq!(None, parse_range("456-123".to_string()));
+        assert_eq!(
+            Some(Range { start: 7, end: 7 }),
+            parse_range("7-7".to_string())
+        );
+    }
 
+    #[test]
+    fn part2_full_domain() {
+        // Arrange
+        let input = [
+            "0-5",
+            "3-18446744073709551615",
+            "18446744073709551615-18446744073709551615",
+            "",
+            "1",
+        ]
+        .join("\n");
+
+        // Act
+        let res = part2_impl(BufReader::new(input.as_bytes())).unwrap();
+
+        // Assert
+        assert_eq!(1_u128 << 64, res)
+    }
+
+    #[test]
+    fn gap_report_example() {
+        // Arrange
+        let input = [
+            "3-5", "10-14", "16-20", "12-18", "", "1", "5", "8", "11", "17", "32",
+        ]
+        .join("\n");
+
+        // Act
+        let report = gap_report(
+            BufReader::new(input.as_bytes()),
+            Range { start: 0, end: 25 },
+        )
+        .unwrap();
+
+        // Assert
         assert_eq!(
-            Some(Range {
-                start: 123,
-                end: 456
-            }),
-            result
+            vec![
+                Range { start: 0, end: 2 },
+                Range { start: 6, end: 9 },
+                Range { start: 21, end: 25 }
+            ],
+            report.gaps
+        );
+        assert_eq!(12, report.total);
+        assert_eq!(Some(Range { start: 21, end: 25 }), report.largest);
+        assert_eq!(
+            "0-2 (3 IDs)\n6-9 (4 IDs)\n21-25 (5 IDs)\n12 spoiled IDs in 3 gaps\nlargest gap: 21-25 (5 IDs)\n",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn gap_report_ties_and_no_gaps() {
+        // Arrange
+        let input = ["3-5", "8-9", "", "4"].join("\n");
+
+        // Act
+        let tied = gap_report(
+            BufReader::new(input.as_bytes()),
+            Range { start: 1, end: 11 },
         )
+        .unwrap();
+        let covered =
+            gap_report(BufReader::new(input.as_bytes()), Range { start: 3, end: 5 }).unwrap();
+
+        // Assert
+        assert_eq!(Some(Range { start: 1, end: 2 }), tied.largest);
+        assert_eq!(None, covered.largest);
+        assert_eq!(0, covered.total);
     }
 
     #[test]
-    fn range_contains() {
+    fn ranges_only_inputs() {
         // Arrange
-        let range = Range { start: 1, end: 2 };
+        let input = "3-5\n10-14\n";
+
+        // Act
+        let report = gap_report(
+            BufReader::new(input.as_bytes()),
+            Range { start: 0, end: 15 },
+        )
+        .unwrap();
+        let provenance = parse_provenance(BufReader::new(input.as_bytes())).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                Range { start: 0, end: 2 },
+                Range { start: 6, end: 9 },
+                Range { start: 15, end: 15 }
+            ],
+            report.gaps
+        );
+        assert_eq!(2, provenance.len());
+    }
 
-        // Act / Assert
-        assert!(!range.contains(0));
-        assert!(range.contains(1));
-        assert!(range.contains(2));
-        assert!(!range.contains(3));
+    #[test]
+    fn parse_ranges_stops_at_the_separator() {
+        // Act
+        let ranges = parse_ranges(BufReader::new(&b"\n3-5\n\nnot an ID\n\xff"[..])).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![SourceRange {
+                line: 2,
+                range: Range { start: 3, end: 5 }
+            }],
+            ranges
+        );
+        assert!(parse_ranges(BufReader::new(&b"3-5\n4\n"[..])).is_err());
     }
 
     #[test]
-    fn vec_ranges_contains_single() {
+    fn run_commands_answers_online() {
         // Arrange
-        let ranges = vec![Range { start: 1, end: 2 }];
+        let input = ["+3-5", "?4", "-4-4", "?4", "?5", "", "+4-10", "?4", "?11"].join("\n");
+        let mut out = vec![];
+
+        // Act
+        let inventory = run_commands(BufReader::new(input.as_bytes()), &mut out).unwrap();
 
-        // Act / Assert
-        assert!(!ranges.contains(0));
-        assert!(ranges.contains(1));
-        assert!(ranges.contains(2));
-        assert!(!ranges.contains(3));
+        // Assert
+        assert_eq!(
+            "4: fresh\n4: spoiled\n5: fresh\n4: fresh\n11: spoiled\n",
+            String::from_utf8(out).unwrap()
+        );
+        assert_eq!(
+            vec![Range { start: 3, end: 10 }],
+            inventory.ranges().collect::<Vec<_>>()
+        );
+    }
+
+    /// What had been written each time the writer was flushed.
+    #[derive(Default)]
+    struct Flushes {
+        written: Vec<u8>,
+        flushed: Vec<String>,
+    }
+
+    impl Write for Flushes {
+        fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> IoResult<()> {
+            self.flushed
+                .push(String::from_utf8_lossy(&self.written).into_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_commands_flushes_every_answer() {
+        // Arrange
+        let input = ["+3-5", "?4", "-4-4", "+7-8", "?4"].join("\n");
+        let mut out = Flushes::default();
+
+        // Act
+        run_commands(BufReader::new(input.as_bytes()), &mut out).unwrap();
+
+        // Assert
+        assert_eq!(vec!["4: fresh\n", "4: fresh\n4: spoiled\n"], out.flushed);
+    }
+
+    #[test]
+    fn run_commands_rejects_unknown_lines() {
+        // Act
+        let result = run_commands(BufReader::new("+1-2\n*3".as_bytes()), &mut vec![]);
+
+        // Assert
+        assert_eq!(
+            "line 2: \"*3\" is not a command",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn inventory_matches_interval_set() {
+        // Arrange
+        let mut inventory = Inventory::new();
+        let mut reference = IntervalSet::new();
+        let mut rng = Rng::new(43);
+
+        for _ in 0..2000 {
+            let start = rng.range(0, 199);
+            let range = Range {
+                start,
+                end: start + rng.range(0, 14),
+            };
+
+            // Act
+            if rng.chance(1, 3) {
+                inventory.remove(range);
+                reference.remove(range);
+            } else {
+                inventory.add(range);
+                reference.insert(range);
+            }
+
+            // Assert
+            assert_eq!(
+                reference.ranges(),
+                inventory.ranges().collect::<Vec<_>>().as_slice()
+            );
+        }
+
+        for id in 0..220 {
+            assert_eq!(reference.contains(id), inventory.is_fresh(id));
+        }
+    }
+
+    #[test]
+    fn inventory_at_the_edges_of_the_domain() {
+        // Arrange
+        let mut inventory = Inventory::new();
+
+        // Act
+        inventory.add(Range {
+            start: u64::MAX - 1,
+            end: u64::MAX,
+        });
+        inventory.add(Range {
+            start: 0,
+            end: u64::MAX - 2,
+        });
+        inventory.remove(Range {
+            start: u64::MAX,
+            end: u64::MAX,
+        });
+
+        // Assert
+        assert_eq!(
+            vec![Range {
+                start: 0,
+                end: u64::MAX - 1
+            }],
+            inventory.ranges().collect::<Vec<_>>()
+        );
+        assert!(!inventory.is_fresh(u64::MAX));
+    }
+
+    /// The `DatabaseError` behind a failed `parse`.
+    fn parse_error(input: &str) -> DatabaseError {
+        parse(BufReader::new(input.as_bytes()))
+            .unwrap_err()
+            .into_inner()
+            .and_then(|e| e.downcast::<DatabaseError>().ok())
+            .map(|e| *e)
+            .unwrap()
     }
 
     #[test]
-    fn vec_ranges_contains_two() {
+    fn parse_handles_crlf_and_whitespace_separators() {
         // Arrange
-        let ranges = vec![Range { start: 1, end: 2 }, Range { start: 4, end: 5 }];
-
-        // Act / Assert
-        assert!(!ranges.contains(0));
-        assert!(ranges.contains(1));
-        assert!(ranges.contains(2));
-        assert!(!ranges.contains(3));
-        assert!(ranges.contains(4));
-        assert!(ranges.contains(5));
-        assert!(!ranges.contains(6));
+        let input = "\r\n3-5\r\n10-14\r\n \t\r\n\r\n5\r\n11\r\n\r\n";
+
+        // Act
+        let database = parse(BufReader::new(input.as_bytes())).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                (2, Range { start: 3, end: 5 }),
+                (3, Range { start: 10, end: 14 })
+            ],
+            database
+                .ranges
+                .iter()
+                .map(|source| (source.line, source.range))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![5, 11], database.ingredients);
+    }
+
+    #[test]
+    fn parse_fails_on_lines_that_are_not_utf8() {
+        // Act
+        let result = parse(BufReader::new(&b"3-5\n\xff\n\n4\n"[..]));
+
+        // Assert
+        assert_eq!(ErrorKind::InvalidData, result.unwrap_err().kind());
+    }
+
+    #[test]
+    fn parse_rejects_malformed_sections() {
+        assert_eq!(
+            DatabaseError::MissingSeparator { line: 3 },
+            parse_error("3-5\n10-14\n5\n11")
+        );
+        assert_eq!(
+            DatabaseError::MissingSection {
+                section: "ingredients"
+            },
+            parse_error("3-5\n10-14\n\n")
+        );
+        assert_eq!(
+            DatabaseError::MissingSection { section: "ranges" },
+            parse_error("\n\n")
+        );
+        assert_eq!(
+            DatabaseError::ExtraSection { line: 6 },
+            parse_error("3-5\n\n4\n5\n\n6")
+        );
+        assert_eq!(
+            DatabaseError::InvalidRange {
+                line: 2,
+                text: "5-3".to_string()
+            },
+            parse_error("3-5\n5-3\n\n4")
+        );
+        assert_eq!(
+            DatabaseError::InvalidIngredient {
+                line: 3,
+                text: "x".to_string()
+            },
+            parse_error("3-5\n\nx")
+        );
     }
 }
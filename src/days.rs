@@ -0,0 +1,137 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    path::Path,
+};
+
+use crate::{day01, day02, day03, day04, day05, input, input::Inputs};
+
+/// The answers to both parts of a puzzle, as they are printed.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Answers {
+    pub part1: String,
+    pub part2: String,
+}
+
+impl Answers {
+    pub fn new(part1: impl ToString, part2: impl ToString) -> Self {
+        Answers {
+            part1: part1.to_string(),
+            part2: part2.to_string(),
+        }
+    }
+
+    /// Reads the expected answers stored next to an input, one line per part.
+    pub fn read(file_path: impl AsRef<Path>) -> IoResult<Self> {
+        let file_path = file_path.as_ref();
+        let text = input::read(file_path)?;
+        let mut lines = text.lines().map(str::trim);
+
+        match (
+            lines.next(),
+            lines.next(),
+            lines.find(|line| !line.is_empty()),
+        ) {
+            (Some(part1), Some(part2), None) => Ok(Answers::new(part1, part2)),
+            _ => Err(IoError::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} should hold exactly two lines, the answers to part 1 and part 2",
+                    file_path.display()
+                ),
+            )),
+        }
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "part 1 = {}, part 2 = {}", self.part1, self.part2)
+    }
+}
+
+/// A registered day, which the runner and the example tests go through.
+pub struct Day {
+    pub number: u8,
+    /// Reads the input file and prints both answers.
+    pub run: fn(&Path) -> IoResult<()>,
+    /// Solves both parts of an already loaded input.
+    pub solve: fn(&str) -> IoResult<Answers>,
+}
+
+impl Day {
+    /// Solves the `variant` input of this day and compares the answers with the ones stored
+    /// next to it, failing with both sets of answers when they differ.
+    pub fn check(&self, inputs: &Inputs, variant: &str) -> IoResult<Answers> {
+        let input = input::read(inputs.locate(self.number, Some(variant))?)?;
+        let expected = Answers::read(inputs.answers_path(self.number, Some(variant)))?;
+
+        let actual = (self.solve)(&input)?;
+        if actual != expected {
+            return Err(IoError::other(format!(
+                "day {:02} {}: expected {}, got {}",
+                self.number, variant, expected, actual
+            )));
+        }
+        Ok(actual)
+    }
+}
+
+pub const DAYS: [Day; 5] = [
+    Day {
+        number: 1,
+        run: day01::day01,
+        solve: day01::solve,
+    },
+    Day {
+        number: 2,
+        run: day02::day02,
+        solve: day02::solve,
+    },
+    Day {
+        number: 3,
+        run: day03::day03,
+        solve: day03::solve,
+    },
+    Day {
+        number: 4,
+        run: day04::day04,
+        solve: day04::solve,
+    },
+    Day {
+        number: 5,
+        run: day05::day05,
+        solve: day05::solve,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::days::DAYS;
+    use crate::input::Inputs;
+
+    #[test]
+    fn examples() {
+        // Arrange
+        let inputs = Inputs::new(concat!(env!("CARGO_MANIFEST_DIR"), "/input"));
+
+        for day in &DAYS {
+            // Act
+            let result = day.check(&inputs, "example");
+
+            // Assert
+            if let Err(error) = result {
+                panic!("{}", error);
+            }
+        }
+    }
+
+    #[test]
+    fn days_are_registered_in_order() {
+        // Act
+        let numbers: Vec<u8> = DAYS.iter().map(|day| day.number).collect();
+
+        // Assert
+        assert_eq!(vec![1, 2, 3, 4, 5], numbers);
+    }
+}
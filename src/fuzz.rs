@@ -0,0 +1,163 @@
+//! Entry points for fuzzing. Each one feeds arbitrary bytes through a day's parsers and
+//! solver and ignores the errors, so any panic is a bug. The `fuzz/` crate wraps them as
+//! cargo-fuzz targets, run with e.g. `cargo fuzz run day05` from the repository root.
+
+use std::io::{self, BufReader};
+
+use crate::{
+    day01, day02, day03,
+    day03::{Highlight, OnInvalid},
+    day04,
+    day04::{DepthMap, Rule},
+    day05,
+    grid::Boundary,
+    input,
+    interval_set::Range,
+};
+
+/// A fuzzing entry point, taking whatever bytes the fuzzer came up with.
+pub type Target = fn(&[u8]);
+
+/// Every entry point with the day it covers.
+pub const TARGETS: [(u8, Target); 5] = [(1, day01), (2, day02), (3, day03), (4, day04), (5, day05)];
+
+pub fn day01(data: &[u8]) {
+    let _ = day01::solve(&text(data));
+}
+
+pub fn day02(data: &[u8]) {
+    let _ = day02::solve(&text(data));
+}
+
+pub fn day03(data: &[u8]) {
+    let _ = day03::solve(&text(data));
+    let _ = day03::parse_banks(BufReader::new(data), 12, OnInvalid::Skip);
+    let _ = day03::validate(BufReader::new(data), 2);
+    let _ = day03::render(
+        BufReader::new(data),
+        2,
+        Highlight::Brackets,
+        &mut io::sink(),
+    );
+}
+
+pub fn day04(data: &[u8]) {
+    let _ = day04::solve(&text(data));
+
+    if let Ok(map) = day04::parse(BufReader::new(data)) {
+        let rule = Rule::default();
+        let _ = DepthMap::new(&map, &rule);
+        let _ = day04::count_removable(&map, &rule, Boundary::Torus);
+    }
+}
+
+pub fn day05(data: &[u8]) {
+    let _ = day05::solve(&text(data));
+
+    let bounds = Range {
+        start: u64::MIN,
+        end: u64::MAX,
+    };
+    let _ = day05::gap_report(BufReader::new(data), bounds);
+    let _ = day05::run_commands(BufReader::new(data), &mut io::sink());
+    let _ = day05::check_ingredients(BufReader::new(data));
+    let _ = day05::parse_provenance(BufReader::new(data));
+}
+
+/// The bytes as the runner would see them after [`input::read`], without rejecting
+/// invalid UTF-8 so the solvers still get to run.
+fn text(data: &[u8]) -> String {
+    input::normalise(&String::from_utf8_lossy(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufReader};
+
+    use crate::day05;
+    use crate::fuzz::TARGETS;
+    use crate::rng::Rng;
+
+    /// Characters the parsers care about, plus multibyte ones to catch slicing mid-character.
+    const ALPHABET: &[&str] = &[
+        "L", "R", "0", "1", "5", "9", "-", ",", "\n", "\r", " ", "@", ".", "€", "ß", "\u{feff}",
+        "+", "?",
+    ];
+
+    /// A day 05 command script, with the odd token from [`ALPHABET`] spliced in so some of
+    /// them fail to parse part way through.
+    fn commands(rng: &mut Rng) -> String {
+        rng.vec(0, 8, |rng| {
+            let mut line = match rng.range(0, 2) {
+                0 => format!("+{}-{}", rng.range(0, 20), rng.range(0, 20)),
+                1 => format!("-{}-{}", rng.range(0, 20), rng.range(0, 20)),
+                _ => format!("?{}", rng.range(0, 20)),
+            };
+            if rng.chance(1, 10) {
+                let at = rng.range(0, line.len() as u64) as usize;
+                line.insert_str(
+                    at,
+                    ALPHABET[rng.range(0, ALPHABET.len() as u64 - 1) as usize],
+                );
+            }
+            line + "\n"
+        })
+        .concat()
+    }
+
+    #[test]
+    fn targets_survive_random_inputs() {
+        let mut rng = Rng::new(49);
+        let mut parsed = 0;
+
+        for _ in 0..2000 {
+            // Arrange
+            let data: Vec<u8> = match rng.range(0, 3) {
+                0 => rng.vec(0, 64, |rng| rng.range(0, 255) as u8),
+                1 => commands(&mut rng).into_bytes(),
+                _ => rng
+                    .vec(0, 40, |rng| {
+                        ALPHABET[rng.range(0, ALPHABET.len() as u64 - 1) as usize]
+                    })
+                    .concat()
+                    .into_bytes(),
+            };
+
+            for (_, target) in TARGETS {
+                // Act / Assert
+                target(&data);
+            }
+            let commands = day05::run_commands(BufReader::new(&data[..]), &mut io::sink());
+            if commands.is_ok() && !data.trim_ascii().is_empty() {
+                parsed += 1;
+            }
+        }
+
+        // Assert
+        assert!(parsed > 0, "no generated input got past the command parser");
+    }
+
+    #[test]
+    fn targets_survive_edge_cases() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"\n\n\n",
+            "L€\nR€5\n€-5,5-€".as_bytes(),
+            b"L-2147483648\nR-2147483648",
+            b"R2147483647\nR2147483647\nL2147483647",
+            b"0-0,1-1,18446744073709551615-0",
+            b"\xff\xfe-\xff",
+            b"9\n99\n0000000000000000000000000000000000000000",
+            b"@@\n@",
+            b"0-18446744073709551615\n\n18446744073709551615",
+            b"+5-3\n-0-18446744073709551615\n?4\n+18446744073709551615-18446744073709551615",
+        ];
+
+        for data in inputs {
+            for (_, target) in TARGETS {
+                // Act / Assert
+                target(data);
+            }
+        }
+    }
+}
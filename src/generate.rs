@@ -0,0 +1,387 @@
+use std::{
+    cmp::min,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error as IoError, ErrorKind, Result as IoResult, Write},
+};
+
+use crate::rng::Rng;
+
+/// The batteries part 2 of day 03 switches on, so every generated bank needs at least as many.
+const PART2_BATTERIES: u64 = 12;
+
+/// Dial rotations for day 01, each by `1..=max_distance` clicks in either direction.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RotationParams {
+    pub rotations: u64,
+    pub max_distance: u64,
+}
+
+impl Default for RotationParams {
+    fn default() -> Self {
+        RotationParams {
+            rotations: 4000,
+            max_distance: 999,
+        }
+    }
+}
+
+/// ID ranges for day 02, starting at `1..=max_start` and spanning up to `max_width` more IDs.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct IdRangeParams {
+    pub ranges: u64,
+    pub max_start: u64,
+    pub max_width: u64,
+}
+
+impl Default for IdRangeParams {
+    fn default() -> Self {
+        IdRangeParams {
+            ranges: 40,
+            max_start: 10_000_000_000,
+            max_width: 1_000_000,
+        }
+    }
+}
+
+/// Banks of batteries for day 03, showing digits `1..=9`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BankParams {
+    pub banks: u64,
+    pub length: u64,
+}
+
+impl Default for BankParams {
+    fn default() -> Self {
+        BankParams {
+            banks: 200,
+            length: 100,
+        }
+    }
+}
+
+/// A grid of rolls for day 04, where each cell holds a roll with `density` percent chance.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct GridParams {
+    pub width: u64,
+    pub height: u64,
+    pub density: u64,
+}
+
+impl Default for GridParams {
+    fn default() -> Self {
+        GridParams {
+            width: 140,
+            height: 140,
+            density: 65,
+        }
+    }
+}
+
+/// A database for day 05, with ranges and ingredient IDs in `0..=max_id`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct DatabaseParams {
+    pub ranges: u64,
+    pub ingredients: u64,
+    pub max_id: u64,
+    pub max_width: u64,
+}
+
+impl Default for DatabaseParams {
+    fn default() -> Self {
+        DatabaseParams {
+            ranges: 180,
+            ingredients: 1000,
+            max_id: 500_000_000_000_000,
+            max_width: 10_000_000_000_000,
+        }
+    }
+}
+
+/// Produces random valid inputs for one day.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Generator {
+    Rotations(RotationParams),
+    IdRanges(IdRangeParams),
+    Banks(BankParams),
+    Grid(GridParams),
+    Database(DatabaseParams),
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum GenerateError {
+    UnknownDay(u8),
+    UnknownParameter {
+        name: String,
+        parameters: &'static [&'static str],
+    },
+    InvalidParameter {
+        name: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            GenerateError::UnknownDay(day) => write!(f, "there is no generator for day {}", day),
+            GenerateError::UnknownParameter { name, parameters } => write!(
+                f,
+                "unknown parameter {:?}, expected one of {}",
+                name,
+                parameters.join(", ")
+            ),
+            GenerateError::InvalidParameter { name, reason } => {
+                write!(f, "{} {}", name, reason)
+            }
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+impl From<GenerateError> for IoError {
+    fn from(error: GenerateError) -> Self {
+        IoError::new(ErrorKind::InvalidInput, error)
+    }
+}
+
+impl Generator {
+    /// The generator for `day` with its default parameters, sized like a real input.
+    pub fn for_day(day: u8) -> Result<Self, GenerateError> {
+        match day {
+            1 => Ok(Generator::Rotations(RotationParams::default())),
+            2 => Ok(Generator::IdRanges(IdRangeParams::default())),
+            3 => Ok(Generator::Banks(BankParams::default())),
+            4 => Ok(Generator::Grid(GridParams::default())),
+            5 => Ok(Generator::Database(DatabaseParams::default())),
+            _ => Err(GenerateError::UnknownDay(day)),
+        }
+    }
+
+    /// The names of the parameters [`Generator::set`] accepts.
+    pub fn parameters(&self) -> &'static [&'static str] {
+        match self {
+            Generator::Rotations(_) => &["rotations", "max-distance"],
+            Generator::IdRanges(_) => &["ranges", "max-start", "max-width"],
+            Generator::Banks(_) => &["banks", "length"],
+            Generator::Grid(_) => &["width", "height", "density"],
+            Generator::Database(_) => &["ranges", "ingredients", "max-id", "max-width"],
+        }
+    }
+
+    /// Sets a parameter by the name used on the command line.
+    pub fn set(&mut self, name: &str, value: u64) -> Result<(), GenerateError> {
+        let parameters = self.parameters();
+        let field = match (self, name) {
+            (Generator::Rotations(p), "rotations") => &mut p.rotations,
+            (Generator::Rotations(p), "max-distance") => {
+                check(value >= 1, "max-distance", "must be at least 1")?;
+                check(
+                    value <= u64::from(u32::MAX),
+                    "max-distance",
+                    "must be at most 4294967295, the largest distance day 01 reads",
+                )?;
+                &mut p.max_distance
+            }
+            (Generator::IdRanges(p), "ranges") => &mut p.ranges,
+            (Generator::IdRanges(p), "max-start") => {
+                check(value >= 1, "max-start", "must be at least 1")?;
+                &mut p.max_start
+            }
+            (Generator::IdRanges(p), "max-width") => &mut p.max_width,
+            (Generator::Banks(p), "banks") => &mut p.banks,
+            (Generator::Banks(p), "length") => {
+                check(
+                    value >= PART2_BATTERIES,
+                    "length",
+                    "must be at least 12, the batteries part 2 switches on",
+                )?;
+                &mut p.length
+            }
+            (Generator::Grid(p), "width") => &mut p.width,
+            (Generator::Grid(p), "height") => &mut p.height,
+            (Generator::Grid(p), "density") => {
+                check(value <= 100, "density", "is a percentage, at most 100")?;
+                &mut p.density
+            }
+            (Generator::Database(p), "ranges") => {
+                check(value >= 1, "ranges", "must be at least 1")?;
+                &mut p.ranges
+            }
+            (Generator::Database(p), "ingredients") => {
+                check(value >= 1, "ingredients", "must be at least 1")?;
+                &mut p.ingredients
+            }
+            (Generator::Database(p), "max-id") => &mut p.max_id,
+            (Generator::Database(p), "max-width") => &mut p.max_width,
+            _ => {
+                return Err(GenerateError::UnknownParameter {
+                    name: name.to_string(),
+                    parameters,
+                });
+            }
+        };
+
+        *field = value;
+        Ok(())
+    }
+
+    /// Writes an input drawn from `seed`; the same seed and parameters give the same input.
+    pub fn write<W>(&self, seed: u64, out: &mut W) -> IoResult<()>
+    where
+        W: Write,
+    {
+        let mut rng = Rng::new(seed);
+
+        match self {
+            Generator::Rotations(p) => {
+                for _ in 0..p.rotations {
+                    let direction = if rng.chance(1, 2) { 'L' } else { 'R' };
+                    writeln!(out, "{}{}", direction, rng.range(1, p.max_distance))?;
+                }
+            }
+            Generator::IdRanges(p) => {
+                for index in 0..p.ranges {
+                    let start = rng.range(1, p.max_start);
+                    let end = start.saturating_add(rng.range(0, p.max_width));
+                    let separator = if index == 0 { "" } else { "," };
+                    write!(out, "{}{}-{}", separator, start, end)?;
+                }
+                writeln!(out)?;
+            }
+            Generator::Banks(p) => {
+                for _ in 0..p.banks {
+                    let bank: String = (0..p.length)
+                        .map(|_| char::from(b'0' + rng.range(1, 9) as u8))
+                        .collect();
+                    writeln!(out, "{}", bank)?;
+                }
+            }
+            Generator::Grid(p) => {
+                for _ in 0..p.height {
+                    let row: String = (0..p.width)
+                        .map(|_| if rng.chance(p.density, 100) { '@' } else { '.' })
+                        .collect();
+                    writeln!(out, "{}", row)?;
+                }
+            }
+            Generator::Database(p) => {
+                for _ in 0..p.ranges {
+                    let start = rng.range(0, p.max_id);
+                    let end = min(start.saturating_add(rng.range(0, p.max_width)), p.max_id);
+                    writeln!(out, "{}-{}", start, end)?;
+                }
+                writeln!(out)?;
+                for _ in 0..p.ingredients {
+                    writeln!(out, "{}", rng.range(0, p.max_id))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check(valid: bool, name: &'static str, reason: &'static str) -> Result<(), GenerateError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(GenerateError::InvalidParameter { name, reason })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::days::DAYS;
+    use crate::generate::GenerateError;
+    use crate::generate::Generator;
+
+    fn generate(generator: &Generator, seed: u64) -> String {
+        let mut out = vec![];
+        generator.write(seed, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn generated_inputs_are_solvable() {
+        for day in &DAYS {
+            // Arrange
+            let generator = Generator::for_day(day.number).unwrap();
+
+            // Act
+            let input = generate(&generator, 5);
+            let result = (day.solve)(&input);
+
+            // Assert
+            assert!(result.is_ok(), "day {}: {:?}", day.number, result);
+        }
+    }
+
+    #[test]
+    fn seeds_are_reproducible() {
+        // Arrange
+        let mut generator = Generator::for_day(4).unwrap();
+        generator.set("width", 8).unwrap();
+        generator.set("height", 3).unwrap();
+
+        // Act
+        let first = generate(&generator, 1);
+        let again = generate(&generator, 1);
+        let other = generate(&generator, 2);
+
+        // Assert
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(vec![8; 3], first.lines().map(str::len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parameters_shape_the_input() {
+        // Arrange
+        let mut generator = Generator::for_day(5).unwrap();
+        generator.set("ranges", 3).unwrap();
+        generator.set("ingredients", 4).unwrap();
+        generator.set("max-id", 20).unwrap();
+
+        // Act
+        let input = generate(&generator, 9);
+
+        // Assert
+        let (ranges, ingredients) = input.split_once("\n\n").unwrap();
+        assert_eq!(3, ranges.lines().count());
+        assert_eq!(4, ingredients.lines().count());
+        assert!(
+            ingredients
+                .lines()
+                .all(|id| id.parse::<u64>().unwrap() <= 20)
+        );
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        // Arrange
+        let mut generator = Generator::for_day(3).unwrap();
+        let mut rotations = Generator::for_day(1).unwrap();
+
+        // Act / Assert
+        assert_eq!(Err(GenerateError::UnknownDay(6)), Generator::for_day(6));
+        assert!(matches!(
+            generator.set("width", 3),
+            Err(GenerateError::UnknownParameter { .. })
+        ));
+        assert!(matches!(
+            generator.set("length", 11),
+            Err(GenerateError::InvalidParameter { name: "length", .. })
+        ));
+        assert_eq!(Ok(()), generator.set("length", 12));
+        assert!(matches!(
+            rotations.set("max-distance", u64::from(u32::MAX) + 1),
+            Err(GenerateError::InvalidParameter {
+                name: "max-distance",
+                ..
+            })
+        ));
+        assert_eq!(Ok(()), rotations.set("max-distance", u64::from(u32::MAX)));
+    }
+}
@@ -0,0 +1,322 @@
+/// A rectangular grid of booleans, stored as one bit per cell.
+///
+/// Every row starts on a fresh `u64`, so a row is a contiguous run of words and
+/// neighbouring rows are a fixed stride apart.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+    boundary: Boundary,
+}
+
+/// What lies beyond the edges of a [`Grid`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Boundary {
+    /// Nothing: cells on the edge simply have fewer neighbours, as in the puzzle. This is also
+    /// how the grid behaves on an infinite empty plane, as removing rolls never adds any.
+    #[default]
+    Bounded,
+    /// The opposite edge, so the grid is a torus.
+    ///
+    /// On grids smaller than the neighbourhood, several offsets can wrap onto the same cell,
+    /// or onto the cell itself, and each of them counts.
+    Torus,
+}
+
+/// The cells counted as neighbours of a cell, as `(row, column)` offsets from it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Neighbourhood {
+    offsets: Vec<(isize, isize)>,
+}
+
+impl Neighbourhood {
+    /// The four cells sharing an edge.
+    pub fn von_neumann() -> Self {
+        Neighbourhood::custom([(-1, 0), (0, -1), (0, 1), (1, 0)])
+    }
+
+    /// The eight cells sharing an edge or a corner.
+    pub fn moore() -> Self {
+        Neighbourhood::radius(1)
+    }
+
+    /// Every cell at most `radius` rows and columns away.
+    pub fn radius(radius: usize) -> Self {
+        let radius = radius as isize;
+        Neighbourhood::custom(
+            (-radius..=radius)
+                .flat_map(|rows| (-radius..=radius).map(move |columns| (rows, columns))),
+        )
+    }
+
+    /// Any set of offsets. A cell is not its own neighbour, so `(0, 0)` is dropped, and
+    /// repeated offsets count once.
+    pub fn custom<I>(offsets: I) -> Self
+    where
+        I: IntoIterator<Item = (isize, isize)>,
+    {
+        let mut offsets: Vec<(isize, isize)> = offsets
+            .into_iter()
+            .filter(|&offset| offset != (0, 0))
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        Neighbourhood { offsets }
+    }
+
+    /// The cells that have a given cell as their neighbour, the same as `self` for
+    /// symmetric neighbourhoods.
+    pub fn reversed(&self) -> Self {
+        Neighbourhood::custom(
+            self.offsets
+                .iter()
+                .map(|&(rows, columns)| (-rows, -columns)),
+        )
+    }
+
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+}
+
+impl Grid {
+    /// A grid of the given size with every cell unset.
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Grid {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+            boundary: Boundary::default(),
+        }
+    }
+
+    /// The same grid, with neighbours found across `boundary`.
+    pub fn with_boundary(self, boundary: Boundary) -> Self {
+        Grid { boundary, ..self }
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Builds a grid from rows of cells; shorter rows are padded with unset cells.
+    pub fn from_rows(rows: &[Vec<bool>]) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let mut grid = Grid::new(width, rows.len());
+
+        for (row, cells) in rows.iter().enumerate() {
+            for (column, &cell) in cells.iter().enumerate() {
+                grid.set(row, column, cell);
+            }
+        }
+
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether `(row, column)` lies inside the grid.
+    pub fn in_bounds(&self, row: usize, column: usize) -> bool {
+        row < self.height && column < self.width
+    }
+
+    /// Panics if `(row, column)` is out of bounds.
+    pub fn get(&self, row: usize, column: usize) -> bool {
+        let (word, bit) = self.locate(row, column);
+        self.bits[word] & bit != 0
+    }
+
+    /// Panics if `(row, column)` is out of bounds.
+    pub fn set(&mut self, row: usize, column: usize, value: bool) {
+        let (word, bit) = self.locate(row, column);
+        if value {
+            self.bits[word] |= bit;
+        } else {
+            self.bits[word] &= !bit;
+        }
+    }
+
+    /// The number of set cells.
+    pub fn count_ones(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The set cells as `(row, column)`, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.bits
+            .chunks_exact(self.words_per_row.max(1))
+            .take(self.height)
+            .enumerate()
+            .flat_map(|(row, words)| {
+                words.iter().enumerate().flat_map(move |(index, &word)| {
+                    BitIter(word).map(move |bit| (row, index * 64 + bit))
+                })
+            })
+    }
+
+    /// The neighbours of `(row, column)` within the grid's boundary, whether they are set or not.
+    pub fn neighbours<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        neighbourhood
+            .offsets
+            .iter()
+            .filter_map(move |&offset| self.offset(row, column, offset))
+    }
+
+    /// The cell `offset` away from `(row, column)`, or `None` if that leaves a bounded grid.
+    fn offset(
+        &self,
+        row: usize,
+        column: usize,
+        (rows, columns): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        match self.boundary {
+            Boundary::Bounded => {
+                let row = row.checked_add_signed(rows)?;
+                let column = column.checked_add_signed(columns)?;
+                self.in_bounds(row, column).then_some((row, column))
+            }
+            Boundary::Torus => Some((
+                (row as isize + rows).rem_euclid(self.height as isize) as usize,
+                (column as isize + columns).rem_euclid(self.width as isize) as usize,
+            )),
+        }
+    }
+
+    fn locate(&self, row: usize, column: usize) -> (usize, u64) {
+        assert!(
+            self.in_bounds(row, column),
+            "({}, {}) is outside a {}x{} grid",
+            row,
+            column,
+            self.width,
+            self.height
+        );
+        (row * self.words_per_row + column / 64, 1 << (column % 64))
+    }
+}
+
+/// The indices of the set bits of a word, lowest first.
+struct BitIter(u64);
+
+impl Iterator for BitIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Boundary;
+    use crate::grid::Grid;
+    use crate::grid::Neighbourhood;
+
+    #[test]
+    fn set_and_get_across_words() {
+        // Arrange
+        let mut grid = Grid::new(130, 3);
+
+        // Act
+        grid.set(0, 0, true);
+        grid.set(1, 63, true);
+        grid.set(1, 64, true);
+        grid.set(2, 129, true);
+        grid.set(2, 129, false);
+
+        // Assert
+        assert!(grid.get(0, 0));
+        assert!(grid.get(1, 63));
+        assert!(grid.get(1, 64));
+        assert!(!grid.get(2, 129));
+        assert_eq!(3, grid.count_ones());
+        assert_eq!(
+            vec![(0, 0), (1, 63), (1, 64)],
+            grid.cells().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn from_rows_pads_short_rows() {
+        // Act
+        let grid = Grid::from_rows(&[vec![true], vec![false, false, true]]);
+
+        // Assert
+        assert_eq!(3, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(vec![(0, 0), (1, 2)], grid.cells().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn neighbours_stay_in_bounds() {
+        // Arrange
+        let grid = Grid::new(3, 3);
+        let moore = Neighbourhood::moore();
+
+        // Act
+        let corner: Vec<_> = grid.neighbours(0, 0, &moore).collect();
+        let centre = grid.neighbours(1, 1, &moore).count();
+
+        // Assert
+        assert_eq!(vec![(0, 1), (1, 0), (1, 1)], corner);
+        assert_eq!(8, centre);
+    }
+
+    #[test]
+    fn neighbours_wrap_on_torus() {
+        // Arrange
+        let grid = Grid::new(4, 3).with_boundary(Boundary::Torus);
+
+        // Act
+        let corner: Vec<_> = grid
+            .neighbours(0, 0, &Neighbourhood::von_neumann())
+            .collect();
+
+        // Assert
+        assert_eq!(vec![(2, 0), (0, 3), (0, 1), (1, 0)], corner);
+    }
+
+    #[test]
+    fn neighbourhood_shapes() {
+        assert_eq!(4, Neighbourhood::von_neumann().offsets().len());
+        assert_eq!(8, Neighbourhood::moore().offsets().len());
+        assert_eq!(24, Neighbourhood::radius(2).offsets().len());
+        assert_eq!(
+            &[(0, 2), (1, 0)],
+            Neighbourhood::custom([(1, 0), (0, 0), (0, 2), (1, 0)]).offsets()
+        );
+        assert_eq!(
+            &[(-1, 0), (0, -2)],
+            Neighbourhood::custom([(1, 0), (0, 2)]).reversed().offsets()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds_panics() {
+        Grid::new(2, 2).get(0, 2);
+    }
+}
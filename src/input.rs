@@ -0,0 +1,181 @@
+use std::{
+    env, fs,
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    path::{Path, PathBuf},
+};
+
+const BOM: char = '\u{feff}';
+
+/// Where the puzzle inputs live, `dayNN.txt` for the real input and `dayNN.<variant>.txt`
+/// for named variants such as `day05.example.txt`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    /// The environment variable naming the input directory.
+    pub const DIR_VAR: &'static str = "AOC25_INPUT_DIR";
+    /// The input directory when nothing else is configured, relative to the working directory.
+    pub const DEFAULT_DIR: &'static str = "input";
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Inputs { dir: dir.into() }
+    }
+
+    /// The directory from [`Inputs::DIR_VAR`], or [`Inputs::DEFAULT_DIR`] when it is unset.
+    pub fn from_env() -> Self {
+        Inputs::new(env::var_os(Self::DIR_VAR).unwrap_or_else(|| Self::DEFAULT_DIR.into()))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path of the input for `day`, whether or not it exists.
+    pub fn path(&self, day: u8, variant: Option<&str>) -> PathBuf {
+        let name = match variant {
+            Some(variant) => format!("day{:02}.{}.txt", day, variant),
+            None => format!("day{:02}.txt", day),
+        };
+        self.dir.join(name)
+    }
+
+    /// The path of the expected answers for an input, `dayNN[.<variant>].answers.txt`.
+    pub fn answers_path(&self, day: u8, variant: Option<&str>) -> PathBuf {
+        self.path(day, variant).with_extension("answers.txt")
+    }
+
+    /// The path of the input for `day`, or a `NotFound` error naming the file that is missing.
+    pub fn locate(&self, day: u8, variant: Option<&str>) -> IoResult<PathBuf> {
+        let path = self.path(day, variant);
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        Err(IoError::new(
+            ErrorKind::NotFound,
+            format!(
+                "no input for day {:02}: {} does not exist (set {} or pass --input-dir to look elsewhere)",
+                day,
+                path.display(),
+                Self::DIR_VAR
+            ),
+        ))
+    }
+}
+
+/// Reads a puzzle input as UTF-8 text, normalised with [`normalise`].
+///
+/// Every day loads its input through here, so none of them has to care about how the file
+/// was saved.
+pub fn read(file_path: impl AsRef<Path>) -> IoResult<String> {
+    let file_path = file_path.as_ref();
+    let bytes = fs::read(file_path).map_err(|error| {
+        IoError::new(error.kind(), format!("{}: {}", file_path.display(), error))
+    })?;
+    let text = String::from_utf8(bytes).map_err(|error| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            format!("{} is not valid UTF-8: {}", file_path.display(), error),
+        )
+    })?;
+
+    Ok(normalise(&text))
+}
+
+/// Drops a leading byte order mark and turns `\r\n` and lone `\r` line endings into `\n`.
+pub fn normalise(text: &str) -> String {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+
+    let mut normalised = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\r' {
+            chars.next_if_eq(&'\n');
+            normalised.push('\n');
+        } else {
+            normalised.push(c);
+        }
+    }
+
+    normalised
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::path::Path;
+
+    use crate::input::Inputs;
+    use crate::input::normalise;
+    use crate::input::read;
+
+    #[test]
+    fn normalise_line_endings() {
+        assert_eq!("L68\nL30\nR48\n", normalise("L68\r\nL30\rR48\n"));
+        assert_eq!("a\n\nb", normalise("a\r\n\r\nb"));
+    }
+
+    #[test]
+    fn normalise_strips_leading_bom_only() {
+        assert_eq!("3-5\n\u{feff}", normalise("\u{feff}3-5\r\n\u{feff}"));
+        assert_eq!("", normalise("\u{feff}"));
+    }
+
+    #[test]
+    fn read_normalises_files() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("aoc25-input-{}.txt", std::process::id()));
+        std::fs::write(&path, "\u{feff}..@\r\n@@.\r\n").unwrap();
+
+        // Act
+        let result = read(path.to_str().unwrap());
+
+        // Assert
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!("..@\n@@.\n", result.unwrap());
+    }
+
+    #[test]
+    fn inputs_name_variants() {
+        // Arrange
+        let inputs = Inputs::new("puzzles");
+
+        // Act / Assert
+        assert_eq!(Path::new("puzzles/day01.txt"), inputs.path(1, None));
+        assert_eq!(
+            Path::new("puzzles/day05.example.txt"),
+            inputs.path(5, Some("example"))
+        );
+        assert_eq!(
+            Path::new("puzzles/day05.example.answers.txt"),
+            inputs.answers_path(5, Some("example"))
+        );
+    }
+
+    #[test]
+    fn locate_names_the_missing_file() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("aoc25-inputs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("day02.txt"), "11-22").unwrap();
+        let inputs = Inputs::new(&dir);
+
+        // Act
+        let found = inputs.locate(2, None);
+        let missing = inputs.locate(2, Some("example"));
+
+        // Assert
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(dir.join("day02.txt"), found.unwrap());
+        let error = missing.unwrap_err();
+        assert_eq!(ErrorKind::NotFound, error.kind());
+        assert!(
+            error
+                .to_string()
+                .contains(&dir.join("day02.example.txt").display().to_string())
+        );
+    }
+}
@@ -0,0 +1,411 @@
+use std::cmp::{max, min};
+
+/// An inclusive range of IDs, `start..=end`. A reversed range, with `start > end`, is empty.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Range {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Range {
+    pub fn contains(&self, id: u64) -> bool {
+        self.start <= id && id <= self.end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    /// The number of IDs in the range, which is `2^64` for the whole `u64` domain.
+    pub fn size(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        u128::from(self.end - self.start) + 1
+    }
+}
+
+/// A set of IDs stored as sorted, disjoint and non-adjacent ranges.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct IntervalSet {
+    ranges: Vec<Range>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    /// The ranges of the set, sorted and merged.
+    pub fn ranges(&self) -> &[Range] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        // The first range that does not end before `id` is the only one that can contain it
+        let index = self.ranges.partition_point(|range| range.end < id);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(id))
+    }
+
+    /// The number of IDs in the set.
+    pub fn coverage(&self) -> u128 {
+        self.ranges.iter().map(Range::size).sum()
+    }
+
+    /// Adds every ID of `range`, merging it with the ranges it touches.
+    pub fn insert(&mut self, range: Range) {
+        if range.is_empty() {
+            return;
+        }
+
+        // The ranges overlapping or adjacent to `range` are the ones to merge with
+        let first = self
+            .ranges
+            .partition_point(|r| r.end.saturating_add(1) < range.start);
+        let last = self
+            .ranges
+            .partition_point(|r| r.start <= range.end.saturating_add(1));
+
+        let merged = self.ranges[first..last]
+            .iter()
+            .fold(range, |merged, r| Range {
+                start: min(merged.start, r.start),
+                end: max(merged.end, r.end),
+            });
+
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Takes out every ID of `range`, splitting the ranges it cuts through.
+    pub fn remove(&mut self, range: Range) {
+        if range.is_empty() {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+
+        if first == last {
+            return;
+        }
+
+        let mut kept = vec![];
+        if self.ranges[first].start < range.start {
+            kept.push(Range {
+                start: self.ranges[first].start,
+                end: range.start - 1,
+            });
+        }
+        if self.ranges[last - 1].end > range.end {
+            kept.push(Range {
+                start: range.end + 1,
+                end: self.ranges[last - 1].end,
+            });
+        }
+
+        self.ranges.splice(first..last, kept);
+    }
+
+    /// The IDs in either set.
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        self.ranges.iter().chain(&other.ranges).copied().collect()
+    }
+
+    /// The IDs in both sets.
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = vec![];
+        let (mut left, mut right) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            let start = max(l.start, r.start);
+            let end = min(l.end, r.end);
+            if start <= end {
+                ranges.push(Range { start, end });
+            }
+
+            // Whichever range ends first cannot overlap anything further
+            if l.end < r.end {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    /// The IDs in `self` but not in `other`.
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        self.intersection(&other.complement(Range {
+            start: u64::MIN,
+            end: u64::MAX,
+        }))
+    }
+
+    /// The IDs within `bounds` that are not in the set.
+    pub fn complement(&self, bounds: Range) -> IntervalSet {
+        let mut ranges = vec![];
+        // The next ID that may start a gap, `None` once we passed `u64::MAX`
+        let mut next = Some(bounds.start);
+
+        for range in &self.ranges {
+            let Some(start) = next else {
+                break;
+            };
+            if range.start > bounds.end {
+                break;
+            }
+            if range.end < start {
+                continue;
+            }
+            if range.start > start {
+                ranges.push(Range {
+                    start,
+                    end: range.start - 1,
+                });
+            }
+            next = range.end.checked_add(1);
+        }
+
+        if let Some(start) = next.filter(|&start| start <= bounds.end) {
+            ranges.push(Range {
+                start,
+                end: bounds.end,
+            });
+        }
+
+        IntervalSet { ranges }
+    }
+}
+
+impl FromIterator<Range> for IntervalSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Range>,
+    {
+        IntervalSet {
+            ranges: merge_ranges(iter.into_iter().collect()),
+        }
+    }
+}
+
+/// Sorts the ranges and merges those that overlap or are adjacent, dropping empty ones.
+fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
+    ranges.retain(|range| !range.is_empty());
+    if ranges.is_empty() {
+        return ranges;
+    }
+
+    ranges.sort_unstable();
+
+    ranges.iter().fold(vec![], |mut merged_ranges, range| {
+        // The ranges are sorted, so we only need to look at the last one
+        match merged_ranges.last_mut() {
+            Some(current) if current.end.saturating_add(1) >= range.start => {
+                // if the last range itersects or is adjacent, we can merge them
+                current.end = max(current.end, range.end)
+            }
+            // otherwise, make a new range
+            _ => merged_ranges.push(*range),
+        };
+        merged_ranges
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interval_set::IntervalSet;
+    use crate::interval_set::Range;
+    use crate::interval_set::merge_ranges;
+    use crate::prop;
+
+    fn set(ranges: &[(u64, u64)]) -> IntervalSet {
+        ranges
+            .iter()
+            .map(|&(start, end)| Range { start, end })
+            .collect()
+    }
+
+    fn pairs(set: &IntervalSet) -> Vec<(u64, u64)> {
+        set.ranges().iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    #[test]
+    fn from_iter_merges_overlapping_and_adjacent() {
+        // Act
+        let result = set(&[(16, 20), (3, 5), (12, 18), (10, 14), (6, 6), (30, 31)]);
+
+        // Assert
+        assert_eq!(vec![(3, 6), (10, 20), (30, 31)], pairs(&result));
+        assert_eq!(17, result.coverage());
+    }
+
+    #[test]
+    fn range_contains() {
+        // Arrange
+        let range = Range { start: 1, end: 2 };
+
+        // Act / Assert
+        assert!(!range.contains(0));
+        assert!(range.contains(1));
+        assert!(range.contains(2));
+        assert!(!range.contains(3));
+    }
+
+    #[test]
+    fn contains_single() {
+        // Arrange
+        let ranges = set(&[(1, 2)]);
+
+        // Act / Assert
+        assert!(!ranges.contains(0));
+        assert!(ranges.contains(1));
+        assert!(ranges.contains(2));
+        assert!(!ranges.contains(3));
+    }
+
+    #[test]
+    fn contains_two() {
+        // Arrange
+        let ranges = set(&[(1, 2), (4, 5)]);
+
+        // Act / Assert
+        assert!(!ranges.contains(0));
+        assert!(ranges.contains(1));
+        assert!(ranges.contains(2));
+        assert!(!ranges.contains(3));
+        assert!(ranges.contains(4));
+        assert!(ranges.contains(5));
+        assert!(!ranges.contains(6));
+    }
+
+    #[test]
+    fn insert_merges_neighbours() {
+        // Arrange
+        let mut ranges = set(&[(1, 2), (5, 6), (10, 12), (20, 25)]);
+
+        // Act
+        ranges.insert(Range { start: 3, end: 10 });
+        ranges.insert(Range { start: 30, end: 30 });
+        ranges.insert(Range { start: 0, end: 0 });
+
+        // Assert
+        assert_eq!(vec![(0, 12), (20, 25), (30, 30)], pairs(&ranges));
+    }
+
+    #[test]
+    fn remove_splits_ranges() {
+        // Arrange
+        let mut ranges = set(&[(1, 10), (15, 20), (25, 30)]);
+
+        // Act
+        ranges.remove(Range { start: 5, end: 16 });
+        ranges.remove(Range { start: 26, end: 27 });
+        ranges.remove(Range { start: 40, end: 50 });
+
+        // Assert
+        assert_eq!(vec![(1, 4), (17, 20), (25, 25), (28, 30)], pairs(&ranges));
+    }
+
+    #[test]
+    fn set_operations() {
+        // Arrange
+        let left = set(&[(1, 5), (10, 15), (20, 20)]);
+        let right = set(&[(4, 11), (14, 22)]);
+
+        // Act / Assert
+        assert_eq!(vec![(1, 22)], pairs(&left.union(&right)));
+        assert_eq!(
+            vec![(4, 5), (10, 11), (14, 15), (20, 20)],
+            pairs(&left.intersection(&right))
+        );
+        assert_eq!(vec![(1, 3), (12, 13)], pairs(&left.difference(&right)));
+        assert_eq!(
+            vec![(6, 9), (16, 19)],
+            pairs(&left.complement(Range { start: 3, end: 19 }))
+        );
+    }
+
+    #[test]
+    fn complement_at_the_edges_of_the_domain() {
+        // Arrange
+        let ranges = set(&[(0, 3), (u64::MAX - 1, u64::MAX)]);
+
+        // Act
+        let result = ranges.complement(Range {
+            start: 0,
+            end: u64::MAX,
+        });
+
+        // Assert
+        assert_eq!(vec![(4, u64::MAX - 2)], pairs(&result));
+        assert!(
+            IntervalSet::new()
+                .complement(Range { start: 5, end: 4 })
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn full_domain_does_not_overflow() {
+        // Arrange
+        let ranges = set(&[(0, 10), (11, u64::MAX), (u64::MAX, u64::MAX)]);
+
+        // Act
+        let coverage = ranges.coverage();
+
+        // Assert
+        assert_eq!(vec![(0, u64::MAX)], pairs(&ranges));
+        assert_eq!(1_u128 << 64, coverage);
+    }
+
+    #[test]
+    fn reversed_ranges_are_empty() {
+        // Arrange
+        let reversed = Range { start: 5, end: 3 };
+        let mut ranges = set(&[(5, 3), (1, 1)]);
+
+        // Act
+        ranges.insert(reversed);
+        ranges.remove(Range { start: 2, end: 0 });
+
+        // Assert
+        assert_eq!(0, reversed.size());
+        assert!(!reversed.contains(4));
+        assert_eq!(vec![(1, 1)], pairs(&ranges));
+    }
+
+    #[test]
+    fn merge_ranges_is_sorted_disjoint_and_covering() {
+        prop::check(
+            500,
+            |rng| rng.vec(0, 10, |rng| (rng.range(0, 40), rng.range(0, 40))),
+            |pairs: &Vec<(u64, u64)>| {
+                let ranges: Vec<Range> = pairs
+                    .iter()
+                    .map(|&(start, end)| Range { start, end })
+                    .collect();
+
+                let merged = merge_ranges(ranges.clone());
+
+                // Sorted and separated by at least one ID, otherwise they would have merged
+                let separated = merged.windows(2).all(|w| w[0].end + 1 < w[1].start);
+                let non_empty = merged.iter().all(|range| !range.is_empty());
+                let covering = (0..=41).all(|id| {
+                    ranges.iter().any(|range| range.contains(id))
+                        == merged.iter().any(|range| range.contains(id))
+                });
+                separated && non_empty && covering
+            },
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::cmp::max;
+
+use crate::interval_set::Range;
+
+/// Possibly overlapping ranges, each with a value, answering which of them contain an ID.
+/// Empty ranges contain nothing and are left out.
+///
+/// The entries are sorted by start and read as an implicit balanced tree, where the middle
+/// of every slice is the root of that slice. Each root also stores the largest end within its
+/// slice, so whole subtrees ending before the ID are skipped.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct IntervalTree<T> {
+    entries: Vec<(Range, T)>,
+    max_ends: Vec<u64>,
+    /// Every end, sorted, to count overlaps without visiting them.
+    ends: Vec<u64>,
+}
+
+impl<T> IntervalTree<T> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every entry whose range contains `id`, sorted by the start of the range.
+    pub fn containing(&self, id: u64) -> Vec<&(Range, T)> {
+        let mut found = vec![];
+        self.collect(0, self.entries.len(), id, &mut found);
+        found
+    }
+
+    /// The number of ranges containing `id`.
+    pub fn overlaps(&self, id: u64) -> usize {
+        // Every range starting at or before `id` contains it, unless it already ended
+        let started = self.entries.partition_point(|(range, _)| range.start <= id);
+        let ended = self.ends.partition_point(|&end| end < id);
+        started - ended
+    }
+
+    fn collect<'a>(&'a self, from: usize, to: usize, id: u64, found: &mut Vec<&'a (Range, T)>) {
+        if from >= to {
+            return;
+        }
+
+        let middle = from + (to - from) / 2;
+        if self.max_ends[middle] < id {
+            return;
+        }
+
+        self.collect(from, middle, id, found);
+
+        let entry = &self.entries[middle];
+        // Everything to the right starts even later
+        if entry.0.start <= id {
+            if entry.0.contains(id) {
+                found.push(entry);
+            }
+            self.collect(middle + 1, to, id, found);
+        }
+    }
+
+    fn build_max_ends(&mut self, from: usize, to: usize) -> u64 {
+        if from >= to {
+            return u64::MIN;
+        }
+
+        let middle = from + (to - from) / 2;
+        let left = self.build_max_ends(from, middle);
+        let right = self.build_max_ends(middle + 1, to);
+        self.max_ends[middle] = max(self.entries[middle].0.end, max(left, right));
+        self.max_ends[middle]
+    }
+}
+
+impl<T> FromIterator<(Range, T)> for IntervalTree<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Range, T)>,
+    {
+        let mut entries: Vec<(Range, T)> = iter
+            .into_iter()
+            .filter(|(range, _)| !range.is_empty())
+            .collect();
+        entries.sort_by_key(|(range, _)| *range);
+
+        let mut ends: Vec<u64> = entries.iter().map(|(range, _)| range.end).collect();
+        ends.sort_unstable();
+
+        let mut tree = IntervalTree {
+            max_ends: vec![u64::MIN; entries.len()],
+            entries,
+            ends,
+        };
+        tree.build_max_ends(0, tree.entries.len());
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interval_set::Range;
+    use crate::interval_tree::IntervalTree;
+
+    #[test]
+    fn containing_matches_linear_search() {
+        // Arrange
+        let ranges: Vec<(Range, usize)> = [
+            (5, 9),
+            (0, 2),
+            (4, 6),
+            (20, 30),
+            (7, 12),
+            (25, 26),
+            (4, 6),
+            (0, 40),
+            (15, 10),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(index, &(start, end))| (Range { start, end }, index))
+        .collect();
+        let tree: IntervalTree<usize> = ranges.iter().copied().collect();
+
+        for id in 0..45 {
+            // Act
+            let mut found: Vec<usize> = tree.containing(id).iter().map(|(_, i)| *i).collect();
+            let overlaps = tree.overlaps(id);
+
+            // Assert
+            found.sort_unstable();
+            let expected: Vec<usize> = ranges
+                .iter()
+                .filter(|(range, _)| range.contains(id))
+                .map(|(_, i)| *i)
+                .collect();
+            assert_eq!(expected, found, "id {id}");
+            assert_eq!(expected.len(), overlaps, "id {id}");
+        }
+    }
+
+    #[test]
+    fn empty_tree() {
+        // Act
+        let tree: IntervalTree<()> = std::iter::empty().collect();
+
+        // Assert
+        assert!(tree.is_empty());
+        assert!(tree.containing(3).is_empty());
+        assert_eq!(0, tree.overlaps(3));
+    }
+}
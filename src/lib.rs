@@ -0,0 +1,15 @@
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod days;
+pub mod fuzz;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod interval_set;
+pub mod interval_tree;
+#[cfg(test)]
+mod prop;
+pub mod rng;
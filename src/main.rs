@@ -1,29 +1,150 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-
-use std::time::Instant;
-
-use crate::day01::day01;
-use crate::day02::day02;
-use crate::day03::day03;
-use crate::day04::day04;
-use crate::day05::day05;
-
-fn main() -> std::io::Result<()> {
-    time(day01, "input/day01.txt")?;
-    time(day02, "input/day02.txt")?;
-    time(day03, "input/day03.txt")?;
-    time(day04, "input/day04.txt")?;
-    time(day05, "input/day05.txt")?;
+use std::{
+    env,
+    io::{self, BufReader, BufWriter, Error as IoError, ErrorKind, Result as IoResult, Write},
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+    time::Instant,
+};
+
+use aoc25::day05;
+use aoc25::days::DAYS;
+use aoc25::generate::Generator;
+use aoc25::input::Inputs;
+
+const USAGE: &str = "usage: aoc25 [--input-dir <dir>] [--variant <name> | --example]
+       aoc25 generate <day> [--seed <n>] [--<parameter> <n>]...
+       aoc25 inventory";
+
+const EXAMPLE: &str = "example";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> IoResult<()> {
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if_eq("generate").is_some() {
+        return generate(args);
+    }
+    if args.next_if_eq("inventory").is_some() {
+        return inventory(args);
+    }
+
+    let options = Options::parse(args)?;
+
+    if options.example {
+        for day in &DAYS {
+            let answers = day.check(&options.inputs, EXAMPLE)?;
+            println!("Day {:02}, example: {} as expected", day.number, answers);
+        }
+        return Ok(());
+    }
+
+    for day in &DAYS {
+        let path = options
+            .inputs
+            .locate(day.number, options.variant.as_deref())?;
+        time(day.run, &path)?;
+    }
     Ok(())
 }
 
-fn time<F>(f: F, input: &str) -> std::io::Result<()>
+/// The command line, where `--input-dir` takes precedence over the environment.
+struct Options {
+    inputs: Inputs,
+    variant: Option<String>,
+    /// Solve the example inputs and check them against their stored answers.
+    example: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> IoResult<Self> {
+        let mut inputs = Inputs::from_env();
+        let mut variant = None;
+        let mut example = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input-dir" => inputs = Inputs::new(value(&arg, args.next())?),
+                "--variant" => variant = Some(value(&arg, args.next())?),
+                "--example" => example = true,
+                _ => return Err(usage(format!("unknown argument \"{}\"", arg))),
+            }
+        }
+
+        if example && variant.is_some() {
+            return Err(usage(
+                "--example already picks the example variant".to_string(),
+            ));
+        }
+
+        Ok(Options {
+            inputs,
+            variant,
+            example,
+        })
+    }
+}
+
+/// Writes a random input for a day to stdout, e.g. `generate 4 --seed 7 --width 1000`.
+fn generate(mut args: impl Iterator<Item = String>) -> IoResult<()> {
+    let day = args
+        .next()
+        .ok_or_else(|| usage("generate needs a day".to_string()))?;
+    let mut generator = Generator::for_day(number(&day)?)?;
+    let mut seed = 0;
+
+    while let Some(arg) = args.next() {
+        let Some(name) = arg.strip_prefix("--") else {
+            return Err(usage(format!("unknown argument \"{}\"", arg)));
+        };
+        let value = number(&value(&arg, args.next())?)?;
+        match name {
+            "seed" => seed = value,
+            _ => generator.set(name, value)?,
+        }
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    generator.write(seed, &mut out)?;
+    out.flush()
+}
+
+/// Runs Day 05 inventory commands such as `+3-5`, `-4-4` and `?4` from stdin, answering
+/// every query on stdout as soon as it is read.
+fn inventory(mut args: impl Iterator<Item = String>) -> IoResult<()> {
+    if let Some(arg) = args.next() {
+        return Err(usage(format!("unknown argument \"{}\"", arg)));
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    day05::run_commands(BufReader::new(io::stdin().lock()), &mut out)?;
+    out.flush()
+}
+
+fn number<T: FromStr>(text: &str) -> IoResult<T> {
+    text.parse()
+        .map_err(|_| usage(format!("\"{}\" is not a number", text)))
+}
+
+fn value(flag: &str, value: Option<String>) -> IoResult<String> {
+    value.ok_or_else(|| usage(format!("{} needs a value", flag)))
+}
+
+fn usage(message: String) -> IoError {
+    IoError::new(ErrorKind::InvalidInput, format!("{}\n{}", message, USAGE))
+}
+
+fn time<F>(f: F, input: &Path) -> IoResult<()>
 where
-    F: Fn(&str) -> std::io::Result<()>,
+    F: Fn(&Path) -> IoResult<()>,
 {
     let start = Instant::now();
 
@@ -0,0 +1,210 @@
+//! A small property-testing layer: generate random inputs from a seed, check a property on
+//! each, and shrink the first failing input to a minimal one before reporting it.
+//!
+//! Properties return `true` for inputs outside their preconditions, so shrinking never
+//! wanders into inputs the property does not care about.
+
+use std::fmt::Debug;
+
+use crate::rng::Rng;
+
+/// The seed of the first case; every case gets its own seed derived from it.
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// How many shrinking steps to take before settling for the smallest input found so far.
+const MAX_SHRINKS: usize = 1000;
+
+/// Values that can propose smaller versions of themselves, the most aggressive first.
+pub trait Shrink: Clone + Debug {
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! shrink_unsigned {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = vec![];
+                if *self > 0 {
+                    smaller.push(0);
+                }
+                if *self / 2 > 0 {
+                    smaller.push(*self / 2);
+                }
+                if *self > 1 {
+                    smaller.push(*self - 1);
+                }
+                smaller
+            }
+        }
+    )*};
+}
+
+macro_rules! shrink_signed {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<Self> {
+                let mut smaller = vec![];
+                if *self != 0 {
+                    smaller.push(0);
+                }
+                if *self < 0 {
+                    smaller.push(-*self);
+                }
+                if *self / 2 != 0 {
+                    smaller.push(*self / 2);
+                }
+                if self.abs() > 1 {
+                    smaller.push(*self - self.signum());
+                }
+                smaller
+            }
+        }
+    )*};
+}
+
+shrink_unsigned!(u32, u64, usize);
+shrink_signed!(i32, i64);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self { vec![false] } else { vec![] }
+    }
+}
+
+impl<T: Shrink> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = vec![];
+
+        // Drop halves first, then single elements, then shrink the elements in place
+        if self.len() > 1 {
+            let half = self.len() / 2;
+            smaller.push(self[half..].to_vec());
+            smaller.push(self[..half].to_vec());
+        }
+        for index in 0..self.len() {
+            let mut without = self.clone();
+            without.remove(index);
+            smaller.push(without);
+        }
+        for (index, element) in self.iter().enumerate() {
+            for shrunk in element.shrink() {
+                let mut with = self.clone();
+                with[index] = shrunk;
+                smaller.push(with);
+            }
+        }
+
+        smaller
+    }
+}
+
+impl<A: Shrink, B: Shrink> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b) = self;
+        a.shrink()
+            .into_iter()
+            .map(|a| (a, b.clone()))
+            .chain(b.shrink().into_iter().map(|b| (a.clone(), b)))
+            .collect()
+    }
+}
+
+impl<A: Shrink, B: Shrink, C: Shrink> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+        ((a.clone(), b.clone()), c.clone())
+            .shrink()
+            .into_iter()
+            .map(|((a, b), c)| (a, b, c))
+            .collect()
+    }
+}
+
+/// Checks `property` on `cases` generated inputs, panicking with the smallest failing input
+/// found by shrinking the first failure.
+pub fn check<T, G, P>(cases: usize, generate: G, property: P)
+where
+    T: Shrink,
+    G: Fn(&mut Rng) -> T,
+    P: Fn(&T) -> bool,
+{
+    for case in 0..cases {
+        let seed = SEED.wrapping_mul(case as u64 + 1);
+        let value = generate(&mut Rng::new(seed));
+
+        if !property(&value) {
+            let minimal = shrink(value.clone(), &property);
+            panic!(
+                "property failed on case {} (seed {:#x})\n  input:  {:?}\n  shrunk: {:?}",
+                case, seed, value, minimal
+            );
+        }
+    }
+}
+
+/// Repeatedly replaces the failing `value` with the first smaller value that still fails.
+fn shrink<T, P>(mut value: T, property: &P) -> T
+where
+    T: Shrink,
+    P: Fn(&T) -> bool,
+{
+    for _ in 0..MAX_SHRINKS {
+        match value
+            .shrink()
+            .into_iter()
+            .find(|smaller| !property(smaller))
+        {
+            Some(smaller) => value = smaller,
+            None => break,
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use crate::prop::Shrink;
+    use crate::prop::check;
+    use crate::prop::shrink;
+
+    #[test]
+    fn shrink_stops_at_the_boundary() {
+        // Arrange
+        let property = |values: &Vec<u32>| values.iter().sum::<u32>() < 10;
+        let failing = vec![3, 8, 1, 7, 2];
+
+        // Act
+        let result = shrink(failing, &property);
+
+        // Assert
+        assert_eq!(10, result.iter().sum::<u32>());
+        assert!(result.shrink().iter().all(property));
+    }
+
+    #[test]
+    fn shrink_signed_goes_towards_zero() {
+        // Act / Assert
+        assert_eq!(vec![0, 5, -2, -4], (-5_i32).shrink());
+        assert!(0_i32.shrink().is_empty());
+        assert_eq!(vec![false], true.shrink());
+    }
+
+    #[test]
+    fn check_reports_the_shrunk_input() {
+        // Act
+        let result = panic::catch_unwind(|| {
+            check(
+                100,
+                |rng| rng.vec(0, 20, |rng| rng.range(0, 100)),
+                |values: &Vec<u64>| !values.contains(&42),
+            )
+        });
+
+        // Assert
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("shrunk: [42]"), "{}", message);
+    }
+}
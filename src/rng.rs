@@ -0,0 +1,78 @@
+/// A xorshift generator, so generated data is varied but reproducible from its seed.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Spread the seed over all bits so nearby seeds diverge right away (splitmix64), and
+        // keep clear of zero, which xorshift never leaves
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        Rng {
+            state: state.max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `low..=high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        match (high - low).checked_add(1) {
+            Some(count) => low + self.next_u64() % count,
+            None => self.next_u64(),
+        }
+    }
+
+    /// `true` with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    /// A vector with a length in `low..=high`, each element drawn by `element`.
+    pub fn vec<T>(
+        &mut self,
+        low: usize,
+        high: usize,
+        mut element: impl FnMut(&mut Rng) -> T,
+    ) -> Vec<T> {
+        let length = self.range(low as u64, high as u64) as usize;
+        (0..length).map(|_| element(self)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::Rng;
+
+    #[test]
+    fn range_stays_in_bounds() {
+        // Arrange
+        let mut rng = Rng::new(7);
+
+        // Act / Assert
+        for _ in 0..1000 {
+            let value = rng.range(3, 5);
+            assert!((3..=5).contains(&value));
+        }
+        rng.range(0, u64::MAX);
+    }
+
+    #[test]
+    fn seeds_are_reproducible_and_distinct() {
+        // Act
+        let first: Vec<u64> = (0..3).map(|seed| Rng::new(seed).next_u64()).collect();
+        let again: Vec<u64> = (0..3).map(|seed| Rng::new(seed).next_u64()).collect();
+
+        // Assert
+        assert_eq!(first, again);
+        assert!(first[0] != first[1] && first[1] != first[2]);
+    }
+}